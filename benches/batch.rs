@@ -0,0 +1,53 @@
+//! Compare building and serializing many codes from shared row data with owned and borrowed fields
+//!
+//! Run with `cargo bench --bench batch`
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use epc_qr_code_generator::{EpcQr, EpcQrRef, Remittance};
+
+const ROWS: usize = 100_000;
+
+fn time(name: &str, f: impl Fn() -> usize) {
+    // warm up
+    black_box(f());
+    let start = Instant::now();
+    black_box(f());
+    let elapsed = start.elapsed();
+    println!("{name:>10}: {elapsed:?} for {ROWS} rows");
+}
+
+fn main() {
+    let rows: Vec<[String; 3]> = (0..ROWS)
+        .map(|row| {
+            [
+                format!("Beneficiary {row}"),
+                format!("DE89370400440532{row:06}"),
+                format!("Invoice {row}"),
+            ]
+        })
+        .collect();
+
+    time("owned", || {
+        rows.iter()
+            .map(|[name, iban, text]| {
+                EpcQr::new(name.clone(), iban.clone())
+                    .with_remittance(Some(Remittance::Text(text.clone())))
+                    .to_string()
+                    .len()
+            })
+            .sum()
+    });
+
+    time("borrowed", || {
+        rows.iter()
+            .map(|[name, iban, text]| {
+                EpcQrRef::new(name, iban)
+                    .with_remittance(Some(Remittance::Text(text)))
+                    .to_string()
+                    .len()
+            })
+            .sum()
+    });
+}
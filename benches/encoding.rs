@@ -0,0 +1,38 @@
+//! Compare encoding ASCII-only payloads with payloads containing accented characters
+//!
+//! Run with `cargo bench --bench encoding`
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use epc_qr_code_generator::{CharacterSet, EpcQr, Remittance};
+
+fn payload_bytes(c: &mut Criterion) {
+    let inputs = [
+        (
+            "ascii",
+            "Max Mustermann",
+            "Rechnung 2023-42 Dank fuer Ihren Einkauf",
+        ),
+        (
+            "accented",
+            "Mäx Müstermann",
+            "Rechnung 2023-42 Dank für Ihren Einkauf",
+        ),
+    ];
+    let character_sets = [CharacterSet::Utf8, CharacterSet::ISO8859_15];
+
+    let mut group = c.benchmark_group("payload_bytes");
+    for (input, name, text) in inputs {
+        for character_set in &character_sets {
+            let code = EpcQr::new(name, "DE89370400440532013000")
+                .with_remittance(Some(Remittance::Text(text)))
+                .with_character_set(character_set.clone());
+            group.bench_function(format!("{input}/{character_set}"), |b| {
+                b.iter(|| black_box(&code).payload_bytes().unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, payload_bytes);
+criterion_main!(benches);
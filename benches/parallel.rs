@@ -0,0 +1,52 @@
+//! Compare rendering many codes sequentially and in parallel on the rayon thread pool
+//!
+//! Run with `cargo bench --features rayon --bench parallel`
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use epc_qr_code_generator::batch::generate_batch_parallel;
+use epc_qr_code_generator::render::RenderOptions;
+use epc_qr_code_generator::{EpcQr, ImageFormat, Remittance};
+
+const CODES: usize = 1_000;
+
+fn time(name: &str, f: impl Fn() -> usize) {
+    // warm up
+    black_box(f());
+    let start = Instant::now();
+    black_box(f());
+    let elapsed = start.elapsed();
+    println!("{name:>10}: {elapsed:?} for {CODES} codes");
+}
+
+fn main() {
+    let codes: Vec<EpcQr> = (0..CODES)
+        .map(|code| {
+            EpcQr::new(
+                format!("Beneficiary {code}"),
+                "DE89370400440532013000".to_owned(),
+            )
+            .with_remittance(Some(Remittance::Text(format!("Invoice {code}"))))
+        })
+        .collect();
+    let format = ImageFormat::png();
+    let options = RenderOptions::default();
+
+    time("sequential", || {
+        codes
+            .iter()
+            .map(|code| {
+                code.generate_image_bytes_with_options(format.clone(), &options)
+                    .map_or(0, |bytes| bytes.len())
+            })
+            .sum()
+    });
+
+    time("parallel", || {
+        generate_batch_parallel(&codes, &format, &options)
+            .into_iter()
+            .map(|bytes| bytes.map_or(0, |bytes| bytes.len()))
+            .sum()
+    });
+}
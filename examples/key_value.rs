@@ -0,0 +1,40 @@
+//! An adapter converting `key=value` lines into an EPC QR-Code, e.g. from a decoded invoice QR-Code
+//!
+//! The keys are the fields of [`GenericPayment`], unknown keys and empty lines are ignored.
+//!
+//! Run with `echo -e "name=Max Mustermann\niban=DE89370400440532013000\namount=12.50" | cargo run --example key_value`
+
+use std::io::Read;
+
+use epc_qr_code_generator::generic::GenericPayment;
+use epc_qr_code_generator::EpcQr;
+
+/// Collect the known keys of `key=value` lines into a [`GenericPayment`]
+fn parse_key_value(text: &str) -> GenericPayment {
+    let mut payment = GenericPayment::default();
+    for (key, value) in text.lines().filter_map(|line| line.split_once('=')) {
+        let field = match key.trim() {
+            "name" => &mut payment.name,
+            "iban" => &mut payment.iban,
+            "bic" => &mut payment.bic,
+            "amount" => &mut payment.amount,
+            "currency" => &mut payment.currency,
+            "purpose" => &mut payment.purpose,
+            "reference" => &mut payment.reference,
+            "message" => &mut payment.message,
+            "info" => &mut payment.info,
+            _ => continue,
+        };
+        *field = Some(value.trim().to_owned());
+    }
+    payment
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+
+    let code = EpcQr::from_generic_payment_fields(parse_key_value(&input))?;
+    println!("{code}");
+    Ok(())
+}
@@ -0,0 +1,13 @@
+#![no_main]
+
+use epc_qr_code_generator::Amount;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    if let Ok(amount) = input.parse::<Amount>() {
+        // every parsed amount is in range and survives a round trip
+        assert!(Amount::MIN.total_cents() <= amount.total_cents());
+        assert!(amount.total_cents() <= Amount::MAX.total_cents());
+        assert_eq!(amount.to_string().parse::<Amount>().unwrap(), amount);
+    }
+});
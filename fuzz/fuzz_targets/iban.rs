@@ -0,0 +1,12 @@
+#![no_main]
+
+use epc_qr_code_generator::{bic_is_valid, find_iban, iban_is_valid};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    iban_is_valid(input);
+    bic_is_valid(input);
+    if let Some(iban) = find_iban(input) {
+        assert!(iban_is_valid(&iban));
+    }
+});
@@ -0,0 +1,26 @@
+#![no_main]
+
+use epc_qr_code_generator::{CharacterSet, EpcQr};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let Ok(code) = input.parse::<EpcQr>() else {
+        return;
+    };
+    // transcode into every character set, unencodable characters must be reported as errors
+    for character_set in [
+        CharacterSet::Utf8,
+        CharacterSet::ISO8859_01,
+        CharacterSet::ISO8859_02,
+        CharacterSet::ISO8859_04,
+        CharacterSet::ISO8859_05,
+        CharacterSet::ISO8859_07,
+        CharacterSet::ISO8859_10,
+        CharacterSet::ISO8859_15,
+    ] {
+        let _ = code
+            .clone()
+            .with_character_set(character_set)
+            .payload_bytes();
+    }
+});
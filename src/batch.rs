@@ -0,0 +1,292 @@
+//! Generate EPC QR-Codes for every row of a CSV file
+//!
+//! The first line of the CSV is a header naming the columns, the order of the columns doesn't matter.
+//! The `name` and `iban` columns are mandatory, `bic`, `amount`, `purpose`, `reference`, `text` and `info`
+//! are optional. Empty cells are treated as unset fields.
+//!
+//! Cells may be quoted with `"` to contain commas, a quote inside a quoted cell is written as `""`.
+//! Cells spanning multiple lines are not supported.
+//!
+//! With the `zip` feature [`write_batch_zip`] collects the images into a single zip archive.
+//! With the `rayon` feature [`generate_batch_parallel`] renders already built codes on multiple threads.
+
+use std::borrow::Cow;
+#[cfg(feature = "zip")]
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Lines, Read};
+#[cfg(feature = "zip")]
+use std::io::{Seek, Write};
+
+#[cfg(feature = "rayon")]
+use crate::render::RenderOptions;
+use crate::{EpcQr, GenerationError, ImageFormat, InvalidEpcCode, Remittance};
+
+/// Information about a generated row
+#[derive(Debug, Clone)]
+pub struct RowMeta {
+    /// The line of the row in the CSV, starting at 1 for the header
+    pub line: usize,
+    /// The file name to use for the generated image, see [`EpcQr::file_name`]
+    pub file_name: String,
+}
+
+/// Lazily generate a PNG image for every row of the CSV read from `reader`
+///
+/// Failing rows produce an error wrapped in [`GenerationError::Row`] without stopping the iteration,
+/// only reading from `reader` failing or an invalid header end it.
+pub fn generate_batch<R: Read>(
+    reader: R,
+) -> impl Iterator<Item = Result<(RowMeta, Vec<u8>), GenerationError>> {
+    Batch {
+        lines: BufReader::new(reader).lines(),
+        line: 0,
+        columns: None,
+        done: false,
+    }
+}
+
+/// Render every code into an image of the given format, spreading the codes across the rayon thread pool
+///
+/// The results are in the same order as `codes`, a failing code doesn't affect the others.
+/// This is equivalent to, but on multiple cores faster than, calling
+/// [`EpcQr::generate_image_bytes_with_options`] for each code in turn.
+#[cfg(feature = "rayon")]
+pub fn generate_batch_parallel<S: AsRef<str> + Sync>(
+    codes: &[EpcQr<S>],
+    format: &ImageFormat,
+    options: &RenderOptions,
+) -> Vec<Result<Vec<u8>, GenerationError>> {
+    use rayon::prelude::*;
+
+    codes
+        .par_iter()
+        .map(|code| code.generate_image_bytes_with_options(format.clone(), options))
+        .collect()
+}
+
+/// Generate a PNG image for every row of the CSV read from `reader` and write them into a zip archive
+///
+/// Next to the images the archive contains a `manifest.csv` with the columns `line`, `file_name` and `status`,
+/// listing every row in order. The status is `ok` for generated images and the error message for failing rows,
+/// which have no file name. If the file name of a row is already used, the line is prepended to it.
+///
+/// Failing rows don't stop the generation, only reading from `reader` or writing the archive failing does.
+/// Returns `writer` after the archive has been finished.
+#[cfg(feature = "zip")]
+pub fn write_batch_zip<R: Read, W: Write + Seek>(
+    reader: R,
+    writer: W,
+) -> Result<W, GenerationError> {
+    let mut archive = zip::ZipWriter::new(writer);
+    // the images are already compressed
+    let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    let mut manifest = String::from("line,file_name,status\n");
+    let mut file_names = HashSet::new();
+    for row in generate_batch(reader) {
+        match row {
+            Ok((meta, data)) => {
+                let file_name = if file_names.contains(&meta.file_name) {
+                    format!("{}-{}", meta.line, meta.file_name)
+                } else {
+                    meta.file_name
+                };
+                archive.start_file(file_name.as_str(), options)?;
+                archive.write_all(&data)?;
+                manifest.push_str(&format!(
+                    "{},{},ok\n",
+                    meta.line,
+                    quote_csv_cell(&file_name)
+                ));
+                file_names.insert(file_name);
+            }
+            Err(GenerationError::Io(err)) => return Err(err.into()),
+            Err(GenerationError::Row { line, source }) => {
+                manifest.push_str(&format!(
+                    "{line},,{}\n",
+                    quote_csv_cell(&source.to_string())
+                ));
+            }
+            Err(err @ GenerationError::InvalidCsv { line, .. }) => {
+                manifest.push_str(&format!("{line},,{}\n", quote_csv_cell(&err.to_string())));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    archive.start_file("manifest.csv", options)?;
+    archive.write_all(manifest.as_bytes())?;
+    Ok(archive.finish()?)
+}
+
+struct Batch<R> {
+    lines: Lines<BufReader<R>>,
+    line: usize,
+    columns: Option<Columns>,
+    done: bool,
+}
+
+struct Columns {
+    name: usize,
+    iban: usize,
+    bic: Option<usize>,
+    amount: Option<usize>,
+    purpose: Option<usize>,
+    reference: Option<usize>,
+    text: Option<usize>,
+    info: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(header: &[String]) -> Result<Self, &'static str> {
+        let find = |name: &str| {
+            header
+                .iter()
+                .position(|column| column.trim().eq_ignore_ascii_case(name))
+        };
+        Ok(Self {
+            name: find("name").ok_or("missing mandatory column 'name'")?,
+            iban: find("iban").ok_or("missing mandatory column 'iban'")?,
+            bic: find("bic"),
+            amount: find("amount"),
+            purpose: find("purpose"),
+            reference: find("reference"),
+            text: find("text"),
+            info: find("info"),
+        })
+    }
+
+    fn epc_qr<'a>(&self, cells: &'a [String]) -> Result<EpcQr<Cow<'a, str>>, GenerationError> {
+        let cell = |column: Option<usize>| {
+            column
+                .and_then(|column| cells.get(column))
+                .map(|cell| cell.trim())
+                .filter(|cell| !cell.is_empty())
+                .map(Cow::Borrowed)
+        };
+
+        let remittance = match (cell(self.reference), cell(self.text)) {
+            (None, Some(text)) => Some(Remittance::Text(text)),
+            (Some(reference), None) => Some(Remittance::Reference(reference)),
+            (None, None) => None,
+            (Some(_), Some(_)) => return Err(InvalidEpcCode::DuplicateRemittance.into()),
+        };
+
+        let amount = cell(self.amount).map(|amount| amount.parse()).transpose()?;
+
+        let iban = cell(Some(self.iban)).unwrap_or_default();
+        let iban = if iban.contains(' ') {
+            Cow::Owned(iban.replace(' ', ""))
+        } else {
+            iban
+        };
+
+        Ok(EpcQr::new(cell(Some(self.name)).unwrap_or_default(), iban)
+            .with_bic(cell(self.bic))
+            .with_amount(amount)
+            .with_purpose(cell(self.purpose))
+            .with_remittance(remittance)
+            .with_info(cell(self.info)))
+    }
+}
+
+impl<R: Read> Iterator for Batch<R> {
+    type Item = Result<(RowMeta, Vec<u8>), GenerationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let line = loop {
+            self.line += 1;
+            match self.lines.next()? {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => break line,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err.into()));
+                }
+            }
+        };
+
+        let cells = match split_csv_line(&line) {
+            Ok(cells) => cells,
+            Err(message) => {
+                self.done = self.columns.is_none();
+                return Some(Err(GenerationError::InvalidCsv {
+                    line: self.line,
+                    message,
+                }));
+            }
+        };
+
+        let Some(columns) = &self.columns else {
+            match Columns::from_header(&cells) {
+                Ok(columns) => self.columns = Some(columns),
+                Err(message) => {
+                    self.done = true;
+                    return Some(Err(GenerationError::InvalidCsv {
+                        line: self.line,
+                        message,
+                    }));
+                }
+            }
+            return self.next();
+        };
+
+        let line = self.line;
+        let row = columns.epc_qr(&cells).and_then(|epc_qr| {
+            let format = ImageFormat::png();
+            let file_name = epc_qr.file_name(&format);
+            let data = epc_qr.generate_image_bytes(format)?;
+            Ok((RowMeta { line, file_name }, data))
+        });
+
+        Some(row.map_err(|err| GenerationError::Row {
+            line,
+            source: Box::new(err),
+        }))
+    }
+}
+
+/// Split a single CSV line into its cells, handling quoted cells
+fn split_csv_line(line: &str) -> Result<Vec<String>, &'static str> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => cell.push(c),
+            (false, '"') if cell.is_empty() => quoted = true,
+            (false, '"') => return Err("unexpected quote inside an unquoted cell"),
+            (false, ',') => cells.push(std::mem::take(&mut cell)),
+            (false, c) => cell.push(c),
+        }
+    }
+
+    if quoted {
+        return Err("unterminated quoted cell");
+    }
+    cells.push(cell);
+
+    Ok(cells)
+}
+
+/// Quote a cell for writing it into a CSV line, if it contains a comma or quote
+#[cfg(feature = "zip")]
+fn quote_csv_cell(cell: &str) -> Cow<'_, str> {
+    if cell.contains([',', '"']) {
+        Cow::Owned(format!("\"{}\"", cell.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(cell)
+    }
+}
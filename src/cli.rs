@@ -0,0 +1,538 @@
+//! The command line interface, usable as a library entry point to embed or test the whole CLI flow
+//!
+//! Build the arguments with [`clap::Parser::parse_from`] and pass them to [`run`].
+
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use qrcode::Version;
+
+use crate::render::{
+    module_size_for_print, parse_color, parse_frame, ChromaSubsampling, ModuleShape, QrCodeInfo,
+    RenderOptions, Rotation,
+};
+#[cfg(feature = "serde")]
+use crate::ValidationReport;
+use crate::{
+    Amount, CharacterSet, EpcQr, GenerationError, ImageFormat, InvalidEpcCode, Remittance,
+};
+
+/// The flags that don't generate a code from the payment arguments, so these are optional with them
+#[cfg(not(feature = "zip"))]
+const WITHOUT_PAYMENT: [&str; 3] = ["example", "list_formats", "check"];
+#[cfg(feature = "zip")]
+const WITHOUT_PAYMENT: [&str; 4] = ["example", "list_formats", "check", "batch"];
+
+#[derive(Debug, clap::Parser)]
+pub struct CliArgs {
+    #[arg(long, short)]
+    bic: Option<String>,
+    #[arg(required_unless_present_any = WITHOUT_PAYMENT)]
+    beneficiary_name: Option<String>,
+    #[cfg_attr(
+        feature = "clipboard",
+        arg(required_unless_present_any = WITHOUT_PAYMENT, required_unless_present = "from_clipboard")
+    )]
+    #[cfg_attr(
+        not(feature = "clipboard"),
+        arg(required_unless_present_any = WITHOUT_PAYMENT)
+    )]
+    beneficiary_account: Option<String>,
+    /// Use the first IBAN found in the clipboard as beneficiary account
+    #[cfg(feature = "clipboard")]
+    #[arg(long, conflicts_with_all = ["beneficiary_account", "example"])]
+    from_clipboard: bool,
+    /// Generate a valid sample code with made-up payment data, e.g. to try out the rendering options
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "beneficiary_name", "beneficiary_account", "bic", "amount", "amount_cents", "amounts",
+            "purpose", "remittance_reference", "remittance_text", "info",
+        ]
+    )]
+    example: bool,
+    /// Amount in euro, either as 12.50 or 12,50
+    #[arg(long, short)]
+    amount: Option<Amount>,
+    /// Amount in integer cents, e.g. 1250 for 12.50 euro
+    #[arg(long, value_parser = parse_amount_cents, conflicts_with_all = ["amount", "amounts"])]
+    amount_cents: Option<Amount>,
+    /// Generate one code per amount, e.g. 5.00,10.00,20.00
+    ///
+    /// As the amounts are separated by commas they have to use '.' as decimal separator.
+    /// Alternatively pass --amounts once per amount.
+    #[arg(long, value_delimiter = ',', conflicts_with = "amount")]
+    amounts: Vec<Amount>,
+    #[arg(long, short)]
+    purpose: Option<String>,
+    #[arg(long = "reference", short = 'r')]
+    remittance_reference: Option<String>,
+    #[arg(long = "text", short = 't')]
+    remittance_text: Option<String>,
+    #[arg(long, short)]
+    info: Option<String>,
+    /// Your own reference for the code, e.g. an invoice number, which is not encoded into the payload
+    ///
+    /// It is printed along with the diagnostics and available as {internal_reference} in --filename-template.
+    #[arg(long)]
+    internal_reference: Option<String>,
+    /// Leave the amount unset, even if --amount, --amount-cents or --amounts is given
+    ///
+    /// The --no-* flags always take precedence over the flags setting the same field.
+    #[arg(long)]
+    no_amount: bool,
+    /// Leave the remittance reference unset, even if --reference is given
+    #[arg(long)]
+    no_reference: bool,
+    /// Leave the info unset, even if --info is given
+    #[arg(long)]
+    no_info: bool,
+    #[arg(long, default_value_t, value_enum)]
+    image_format: ImageFormat,
+    /// Character set used to encode the payload
+    #[arg(long, value_enum, ignore_case = true)]
+    charset: Option<CharacterSet>,
+    /// Template for the name of the generated file
+    ///
+    /// Available placeholders are {account}, {bic}, {amount}, {reference}, {internal_reference} and {ext}.
+    /// Text inside the braces around the placeholder name is only included when the field is set, e.g. {bic-}.
+    ///
+    /// Defaults to "epc-{bic-}{account}{-reference}-qr-code.{ext}",
+    /// with --amounts to "epc-{bic-}{account}{-reference}{-amount}-qr-code.{ext}".
+    #[arg(long)]
+    filename_template: Option<String>,
+    /// Read the generated image back and check that it matches the encoded data
+    #[arg(long)]
+    verify: bool,
+    /// Color of the dark modules as #rrggbb, rrggbb or #rgb
+    #[arg(long, value_parser = parse_color, default_value = "#000000")]
+    dark: [u8; 3],
+    /// Color of the light modules and the quiet zone as #rrggbb, rrggbb or #rgb
+    #[arg(long, value_parser = parse_color, default_value = "#ffffff")]
+    light: [u8; 3],
+    /// Color of the finder patterns in the corners as #rrggbb, rrggbb or #rgb, defaults to the dark color
+    #[arg(long, value_parser = parse_color)]
+    finder_color: Option<[u8; 3]>,
+    /// Color of the quiet zone around the code as #rrggbb, rrggbb or #rgb, defaults to the light color
+    #[arg(long, value_parser = parse_color)]
+    quiet_zone_color: Option<[u8; 3]>,
+    /// Frame around the quiet zone as <width in pixels>:<color>, e.g. 4:000000
+    ///
+    /// The frame is drawn outside the quiet zone, so it doesn't affect scanning.
+    #[arg(long, value_parser = parse_frame)]
+    frame: Option<(u32, [u8; 3])>,
+    /// Distance in centimeters the printed code should be scannable from, needs --dpi
+    ///
+    /// The module size is chosen so that the code printed at --dpi can be scanned from this distance.
+    #[arg(long, requires = "dpi")]
+    scan_distance: Option<f32>,
+    /// Resolution in dots per inch the code will be printed at, needs --scan-distance
+    #[arg(long, requires = "scan_distance")]
+    dpi: Option<u32>,
+    /// Shape of the dark modules, the finder patterns in the corners stay square
+    #[arg(long, value_enum, default_value_t)]
+    shape: ModuleShape,
+    /// Rotate the image clockwise by the given degrees, e.g. for label printers feeding the media sideways
+    #[arg(long, value_enum, default_value_t)]
+    rotate: Rotation,
+    /// Quality of JPEG images from 1 to 100
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=100), default_value_t = 75)]
+    jpeg_quality: u8,
+    /// Chroma subsampling of colored JPEG images, subsampling blurs the colors across module boundaries
+    #[arg(long, value_enum, default_value_t)]
+    chroma_subsampling: ChromaSubsampling,
+    /// Draw grid lines between the modules, for debugging only as the result is not meant to be scanned
+    #[arg(long, conflicts_with = "verify")]
+    debug_grid: bool,
+    /// Only validate and report the payload size and QR-Code version, without writing any file
+    ///
+    /// Exits with a failure code if any code is invalid.
+    #[arg(long)]
+    dry_run: bool,
+    /// Print the report of --dry-run as one JSON object per code to stdout, instead of the payload and summary
+    #[cfg(feature = "serde")]
+    #[arg(long, requires = "dry_run")]
+    json: bool,
+    /// Print the QR-Code version, error correction level and image dimensions to stderr
+    #[arg(long, short, conflicts_with = "quiet")]
+    verbose: bool,
+    /// Don't print the payload
+    #[arg(long, short)]
+    quiet: bool,
+    /// Print the supported image formats and their file extensions, one per line, and exit
+    #[arg(long, exclusive = true)]
+    list_formats: bool,
+    /// Check whether an existing payload is a valid EPC QR-Code, listing its problems, and exit
+    ///
+    /// The payload is read from the file if one exists at the given path, otherwise the argument is the payload.
+    /// Line breaks may be given escaped as \n or \r\n, e.g. as printed by a log. Exits with a failure code if invalid.
+    #[arg(long, exclusive = true, value_name = "PAYLOAD_OR_FILE")]
+    check: Option<String>,
+    /// Generate a PNG image for every row of a CSV file into the archive given with --zip, and exit
+    ///
+    /// The header names the columns, name and iban are mandatory, bic, amount, purpose, reference, text
+    /// and info are optional. Failing rows don't stop the generation, they are listed in the manifest.
+    #[cfg(feature = "zip")]
+    #[arg(
+        long,
+        requires = "zip",
+        value_name = "CSV_FILE",
+        conflicts_with_all = [
+            "beneficiary_name", "beneficiary_account", "example", "amounts", "dry_run",
+        ]
+    )]
+    batch: Option<PathBuf>,
+    /// Zip archive to write the images generated with --batch into, with a manifest.csv listing
+    /// the status and file name of every row
+    #[cfg(feature = "zip")]
+    #[arg(long, requires = "batch", value_name = "OUT_ZIP")]
+    zip: Option<PathBuf>,
+}
+
+/// Generate the codes described by `args`, returning the paths of the written images
+///
+/// The payloads and diagnostics are printed to stdout and stderr like the binary does.
+/// With `--dry-run`, `--list-formats` or `--check` no images are written and the list is empty,
+/// with `--batch` the list only contains the zip archive.
+/// A dry run fails with the first invalid code, after reporting the problems of all codes.
+pub fn run(args: CliArgs) -> Result<Vec<PathBuf>, GenerationError> {
+    if let Some(input) = &args.check {
+        check(input)?;
+        return Ok(Vec::new());
+    }
+
+    #[cfg(feature = "zip")]
+    if let (Some(csv), Some(zip)) = (&args.batch, &args.zip) {
+        let reader = std::fs::File::open(csv)?;
+        let writer = std::io::BufWriter::new(std::fs::File::create(zip)?);
+        crate::batch::write_batch_zip(reader, writer)?
+            .into_inner()
+            .map_err(std::io::IntoInnerError::into_error)?;
+        return Ok(vec![zip.clone()]);
+    }
+
+    if args.list_formats {
+        for format in ImageFormat::SUPPORTED {
+            let name = format
+                .to_possible_value()
+                .expect("all supported formats have a name");
+            println!("{} {}", name.get_name(), format.extension());
+        }
+        return Ok(Vec::new());
+    }
+
+    let epc_qr = if args.example {
+        EpcQr::example()
+    } else {
+        let remittance_reference = args.remittance_reference.filter(|_| !args.no_reference);
+        let remittance = match (remittance_reference, args.remittance_text) {
+            (None, Some(text)) => Some(Remittance::Text(text)),
+            (Some(reference), None) => Some(Remittance::Reference(reference)),
+            (None, None) => None,
+            (Some(_), Some(_)) => {
+                return Err(GenerationError::InvalidEpcCode(
+                    InvalidEpcCode::DuplicateRemittance,
+                ))
+            }
+        };
+
+        let beneficiary_account = match args.beneficiary_account {
+            Some(account) => account.replace(' ', ""),
+            #[cfg(feature = "clipboard")]
+            None => iban_from_clipboard()?,
+            #[cfg(not(feature = "clipboard"))]
+            None => unreachable!("clap requires the account without the clipboard feature"),
+        };
+
+        let beneficiary_name = args
+            .beneficiary_name
+            .expect("clap requires the name unless listing the formats or using the example");
+        EpcQr::new(beneficiary_name, beneficiary_account)
+            .with_bic(args.bic)
+            .with_amount(
+                args.amount
+                    .or(args.amount_cents)
+                    .filter(|_| !args.no_amount),
+            )
+            .with_purpose(args.purpose)
+            .with_remittance(remittance)
+            .with_info(args.info.filter(|_| !args.no_info))
+    };
+    let epc_qr = match args.charset {
+        Some(charset) => epc_qr.with_character_set(charset),
+        None => epc_qr,
+    };
+    let epc_qr = epc_qr.with_internal_reference(args.internal_reference);
+
+    #[cfg(feature = "security")]
+    for warning in epc_qr.warnings() {
+        eprintln!("Warning: {warning}");
+    }
+
+    let mut render_options = RenderOptions::default()
+        .with_verify(args.verify)
+        .with_colors(args.dark, args.light)
+        .with_finder_color(args.finder_color)
+        .with_quiet_zone_color(args.quiet_zone_color)
+        .with_module_shape(args.shape)
+        .with_rotation(args.rotate)
+        .with_jpeg_quality(args.jpeg_quality)
+        .with_chroma_subsampling(args.chroma_subsampling)
+        .with_debug_grid(args.debug_grid);
+    if let Some((width, color)) = args.frame {
+        render_options = render_options.with_frame(width, color);
+    }
+
+    let (codes, default_template) = if args.amounts.is_empty() || args.no_amount {
+        (vec![epc_qr], EpcQr::DEFAULT_FILE_NAME_TEMPLATE)
+    } else {
+        (
+            epc_qr.with_amounts(&args.amounts),
+            EpcQr::DEFAULT_AMOUNTS_FILE_NAME_TEMPLATE,
+        )
+    };
+    let template = args
+        .filename_template
+        .as_deref()
+        .unwrap_or(default_template);
+
+    if let (Some(scan_distance), Some(dpi)) = (args.scan_distance, args.dpi) {
+        // the sparsest code needs the largest modules, invalid codes are reported when generating them
+        let module_size = codes
+            .iter()
+            .filter_map(|epc_qr| epc_qr.qr_code_info(&render_options).ok())
+            .map(|info| module_size_for_print(scan_distance, dpi, info.modules))
+            .max()
+            .unwrap_or(1);
+        render_options = render_options.with_module_size(module_size);
+        eprintln!("Module size {module_size} pixels to scan from {scan_distance} cm when printed at {dpi} dpi");
+    }
+
+    if args.dry_run {
+        let mut first_error = None;
+        for epc_qr in codes {
+            #[cfg(feature = "serde")]
+            if args.json {
+                let payload = epc_qr.payload_bytes();
+                let report = DryRunReport {
+                    payload: epc_qr.to_string(),
+                    internal_reference: epc_qr.internal_reference().map(str::to_owned),
+                    bytes: payload.as_ref().ok().map(Vec::len),
+                    qr_code: epc_qr.qr_code_info(&render_options).ok().map(Into::into),
+                    report: epc_qr.validation_report(),
+                };
+                println!(
+                    "{}",
+                    serde_json::to_string(&report).expect("the report serializes to JSON")
+                );
+                if let Err(err) = payload {
+                    first_error.get_or_insert(err);
+                }
+                continue;
+            }
+            if !args.quiet {
+                println!("{epc_qr}");
+            }
+            let errors = epc_qr.validate_verbose();
+            for error in &errors {
+                eprintln!("{error}");
+            }
+            match epc_qr.payload_bytes() {
+                Ok(payload) => {
+                    let info = epc_qr.qr_code_info(&render_options)?;
+                    eprintln!(
+                        "{}Payload of {} bytes, {}",
+                        internal_reference(&epc_qr),
+                        payload.len(),
+                        describe(&info)
+                    );
+                }
+                // the field errors were already reported above
+                Err(err) if !errors.is_empty() => {
+                    first_error.get_or_insert(err);
+                }
+                Err(err) => {
+                    eprintln!("{err}");
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+        return match first_error {
+            Some(err) => Err(err.into()),
+            None => Ok(Vec::new()),
+        };
+    }
+
+    let mut paths = Vec::with_capacity(codes.len());
+    for epc_qr in codes {
+        if !args.quiet {
+            let epc_qr_string = epc_qr.to_string();
+            println!("{epc_qr_string}");
+        }
+
+        let file_name = epc_qr.file_name_from_template(template, &args.image_format)?;
+
+        epc_qr.generate_image_file_with_options(
+            Some(args.image_format.clone()),
+            file_name.as_ref(),
+            &render_options,
+        )?;
+
+        if let Some(dpi) = args.dpi {
+            let info = epc_qr.qr_code_info(&render_options)?;
+            let mm = |pixels: u32| pixels as f32 / dpi as f32 * 25.4;
+            eprintln!(
+                "{}{}, printed {:.1}x{:.1} mm",
+                internal_reference(&epc_qr),
+                describe(&info),
+                mm(info.width),
+                mm(info.height)
+            );
+        } else if args.verbose {
+            let info = epc_qr.qr_code_info(&render_options)?;
+            eprintln!("{}{}", internal_reference(&epc_qr), describe(&info));
+        }
+
+        paths.push(PathBuf::from(file_name));
+    }
+
+    Ok(paths)
+}
+
+/// Parse and validate the payload given as `input` or in the file at that path, reporting the result
+fn check(input: &str) -> Result<(), GenerationError> {
+    let payload = if Path::new(input).is_file() {
+        std::fs::read_to_string(input)?
+    } else {
+        unescape(input)
+    };
+    // editors and decoders commonly end the file or output with a line break
+    let payload = payload.trim_end_matches(['\r', '\n']);
+
+    let epc_qr = match payload.parse::<EpcQr>() {
+        Ok(epc_qr) => epc_qr,
+        Err(err) => {
+            println!("Invalid EPC QR-Code: {err}");
+            return Err(err.into());
+        }
+    };
+    let errors = epc_qr.validate_verbose();
+    for error in &errors {
+        eprintln!("{error}");
+    }
+    match epc_qr.validate() {
+        Ok(()) => {
+            println!("Valid EPC QR-Code");
+            Ok(())
+        }
+        Err(err) => {
+            // the field errors were already reported above
+            if errors.is_empty() {
+                eprintln!("{err}");
+            }
+            println!("Invalid EPC QR-Code");
+            Err(err.into())
+        }
+    }
+}
+
+/// Undo the escaping of [`EpcQr::to_debug_line`], turning `\n`, `\r` and `\\` back into the characters
+fn unescape(line: &str) -> String {
+    let mut payload = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            payload.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => payload.push('\n'),
+            Some('r') => payload.push('\r'),
+            Some('\\') => payload.push('\\'),
+            Some(other) => {
+                payload.push('\\');
+                payload.push(other);
+            }
+            None => payload.push('\\'),
+        }
+    }
+    payload
+}
+
+/// Parse an amount given in integer cents
+fn parse_amount_cents(cents: &str) -> Result<Amount, String> {
+    let cents = cents.parse::<u64>().map_err(|err| err.to_string())?;
+    Amount::from_cents(cents).map_err(|err| err.to_string())
+}
+
+/// The internal reference of `epc_qr` as prefix of its diagnostics, e.g. `INV-42: `, empty without one
+fn internal_reference(epc_qr: &EpcQr) -> String {
+    epc_qr
+        .internal_reference()
+        .map_or_else(String::new, |reference| format!("{reference}: "))
+}
+
+/// The machine-readable report of a single code for `--dry-run --json`
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct DryRunReport {
+    payload: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    internal_reference: Option<String>,
+    /// The size of the encoded payload, if it can be encoded
+    bytes: Option<usize>,
+    /// The generated QR-Code and image, if the code is valid
+    qr_code: Option<QrCodeReport>,
+    #[serde(flatten)]
+    report: ValidationReport,
+}
+
+/// The serializable parts of [`QrCodeInfo`]
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct QrCodeReport {
+    version: i16,
+    ec_level: String,
+    modules: usize,
+    width: u32,
+    height: u32,
+}
+
+#[cfg(feature = "serde")]
+impl From<QrCodeInfo> for QrCodeReport {
+    fn from(info: QrCodeInfo) -> Self {
+        let version = match info.version {
+            Version::Normal(version) | Version::Micro(version) => version,
+        };
+        Self {
+            version,
+            ec_level: format!("{:?}", info.ec_level),
+            modules: info.modules,
+            width: info.width,
+            height: info.height,
+        }
+    }
+}
+
+/// A short human-readable description of the generated QR-Code and image
+fn describe(info: &QrCodeInfo) -> String {
+    let version = match info.version {
+        Version::Normal(version) | Version::Micro(version) => version,
+    };
+    format!(
+        "QR-Code version {version}, error correction level {:?}, {modules}x{modules} modules, image {}x{} pixels",
+        info.ec_level,
+        info.width,
+        info.height,
+        modules = info.modules,
+    )
+}
+
+/// Read the clipboard and extract the first IBAN from it
+///
+/// Fails with [`GenerationError::NoIbanInClipboard`] if there is none.
+#[cfg(feature = "clipboard")]
+fn iban_from_clipboard() -> Result<String, GenerationError> {
+    let text = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text())?;
+    crate::find_iban(&text).ok_or(GenerationError::NoIbanInClipboard)
+}
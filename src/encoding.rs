@@ -0,0 +1,51 @@
+//! Encoding of the payload in the character sets allowed by the EPC QR-Code specification
+//!
+//! All supported ISO 8859 parts match ASCII and the C1 control characters below `0xA0`,
+//! so only the upper 96 characters of each part are listed, `\0` marks unassigned bytes.
+
+use crate::CharacterSet;
+
+const ISO8859_02: &str =
+    "\u{a0}Ą˘Ł¤ĽŚ§¨ŠŞŤŹ\u{ad}ŽŻ°ą˛ł´ľśˇ¸šşťź˝žżŔÁÂĂÄĹĆÇČÉĘËĚÍÎĎĐŃŇÓÔŐÖ×ŘŮÚŰÜÝŢßŕáâăäĺćçčéęëěíîďđńňóôőö÷řůúűüýţ˙";
+const ISO8859_04: &str =
+    "\u{a0}ĄĸŖ¤ĨĻ§¨ŠĒĢŦ\u{ad}Ž¯°ą˛ŗ´ĩļˇ¸šēģŧŊžŋĀÁÂÃÄÅÆĮČÉĘËĖÍÎĪĐŅŌĶÔÕÖ×ØŲÚÛÜŨŪßāáâãäåæįčéęëėíîīđņōķôõö÷øųúûüũū˙";
+const ISO8859_05: &str =
+    "\u{a0}ЁЂЃЄЅІЇЈЉЊЋЌ\u{ad}ЎЏАБВГДЕЖЗИЙКЛМНОПРСТУФХЦЧШЩЪЫЬЭЮЯабвгдежзийклмнопрстуфхцчшщъыьэюя№ёђѓєѕіїјљњћќ§ўџ";
+const ISO8859_07: &str =
+    "\u{a0}‘’£€₯¦§¨©ͺ«¬\u{ad}\0―°±²³΄΅Ά·ΈΉΊ»Ό½ΎΏΐΑΒΓΔΕΖΗΘΙΚΛΜΝΞΟΠΡ\0ΣΤΥΦΧΨΩΪΫάέήίΰαβγδεζηθικλμνξοπρςστυφχψωϊϋόύώ\0";
+const ISO8859_10: &str =
+    "\u{a0}ĄĒĢĪĨĶ§ĻĐŠŦŽ\u{ad}ŪŊ°ąēģīĩķ·ļđšŧž―ūŋĀÁÂÃÄÅÆĮČÉĘËĖÍÎÏÐŅŌÓÔÕÖŨØŲÚÛÜÝÞßāáâãäåæįčéęëėíîïðņōóôõöũøųúûüýþĸ";
+const ISO8859_15: &str =
+    "\u{a0}¡¢£€¥Š§š©ª«¬\u{ad}®¯°±²³Žµ¶·ž¹º»ŒœŸ¿ÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏÐÑÒÓÔÕÖ×ØÙÚÛÜÝÞßàáâãäåæçèéêëìíîïðñòóôõö÷øùúûüýþÿ";
+
+/// Encode `text` in `character_set`, returning the first character that can't be represented on failure
+pub(crate) fn encode(character_set: &CharacterSet, text: &str) -> Result<Vec<u8>, char> {
+    // ASCII is encoded the same in all character sets, which skips the lookup for the common case
+    if text.is_ascii() {
+        return Ok(text.as_bytes().to_vec());
+    }
+
+    let upper_half = match character_set {
+        CharacterSet::Utf8 => return Ok(text.as_bytes().to_vec()),
+        CharacterSet::ISO8859_01 => None,
+        CharacterSet::ISO8859_02 => Some(ISO8859_02),
+        CharacterSet::ISO8859_04 => Some(ISO8859_04),
+        CharacterSet::ISO8859_05 => Some(ISO8859_05),
+        CharacterSet::ISO8859_07 => Some(ISO8859_07),
+        CharacterSet::ISO8859_10 => Some(ISO8859_10),
+        CharacterSet::ISO8859_15 => Some(ISO8859_15),
+    };
+
+    text.chars()
+        .map(|c| match (u8::try_from(c), upper_half) {
+            (Ok(byte), _) if byte < 0xA0 => Ok(byte),
+            (Ok(byte), None) => Ok(byte),
+            (_, Some(upper_half)) => upper_half
+                .chars()
+                .position(|candidate| candidate == c)
+                .map(|idx| 0xA0 + idx as u8)
+                .ok_or(c),
+            (Err(_), None) => Err(c),
+        })
+        .collect()
+}
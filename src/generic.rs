@@ -0,0 +1,83 @@
+//! Convert payment data read from other formats, e.g. other payment QR-Codes, into EPC QR-Codes
+//!
+//! Adapters for other formats fill a [`GenericPayment`], which [`EpcQr::from_generic_payment_fields`]
+//! maps to the EPC fields. See `examples/key_value.rs` for an adapter reading `key=value` lines.
+//!
+//! | [`GenericPayment`] | [`EpcQr`]                                          |
+//! |--------------------|----------------------------------------------------|
+//! | `name`             | beneficiary name (required)                        |
+//! | `iban`             | beneficiary account (required), spaces are removed |
+//! | `bic`              | BIC                                                |
+//! | `amount`           | amount, e.g. `12.50`, `12,50` or `12`              |
+//! | `currency`         | must be `EUR` if set                               |
+//! | `purpose`          | purpose                                            |
+//! | `reference`        | remittance reference                               |
+//! | `message`          | remittance text                                    |
+//! | `info`             | beneficiary to originator information              |
+
+use crate::{Amount, EpcQr, InvalidAmount, InvalidEpcCode, Remittance};
+
+/// Payment fields independent of a specific format, see the [module documentation](crate::generic)
+#[derive(Debug, Clone, Default)]
+pub struct GenericPayment {
+    pub name: Option<String>,
+    pub iban: Option<String>,
+    pub bic: Option<String>,
+    pub amount: Option<String>,
+    pub currency: Option<String>,
+    pub purpose: Option<String>,
+    pub reference: Option<String>,
+    pub message: Option<String>,
+    pub info: Option<String>,
+}
+
+impl EpcQr {
+    /// Map generic payment fields to an EPC QR-Code and validate it
+    ///
+    /// See the [module documentation](crate::generic) for which fields map where.
+    pub fn from_generic_payment_fields(
+        payment: GenericPayment,
+    ) -> Result<Self, GenericPaymentError> {
+        let name = payment
+            .name
+            .ok_or(GenericPaymentError::MissingField("name"))?;
+        let iban = payment
+            .iban
+            .ok_or(GenericPaymentError::MissingField("iban"))?;
+        if let Some(currency) = payment.currency.filter(|currency| currency != "EUR") {
+            return Err(GenericPaymentError::UnsupportedCurrency(currency));
+        }
+        let amount = payment
+            .amount
+            .map(|amount| Amount::from_payload_str(&amount))
+            .transpose()?;
+        let remittance = match (payment.reference, payment.message) {
+            (Some(reference), None) => Some(Remittance::Reference(reference)),
+            (None, Some(message)) => Some(Remittance::Text(message)),
+            (None, None) => None,
+            (Some(_), Some(_)) => return Err(InvalidEpcCode::DuplicateRemittance.into()),
+        };
+
+        let code = EpcQr::new(name, iban.replace(' ', ""))
+            .with_bic(payment.bic)
+            .with_amount(amount)
+            .with_purpose(payment.purpose)
+            .with_remittance(remittance)
+            .with_info(payment.info);
+        code.validate()?;
+        Ok(code)
+    }
+}
+
+/// Errors when converting a [`GenericPayment`] with [`EpcQr::from_generic_payment_fields`]
+#[derive(Debug, thiserror::Error)]
+pub enum GenericPaymentError {
+    #[error("Missing required field {0}")]
+    MissingField(&'static str),
+    #[error("Only amounts in EUR are supported, but the currency was '{0}'")]
+    UnsupportedCurrency(String),
+    #[error("{0}")]
+    InvalidAmount(#[from] InvalidAmount),
+    #[error("{0}")]
+    InvalidEpcCode(#[from] InvalidEpcCode),
+}
@@ -1,17 +1,30 @@
+pub mod batch;
+#[cfg(feature = "cli")]
+pub mod cli;
+mod encoding;
+mod font;
+pub mod generic;
+#[cfg(feature = "sepa-xml")]
+pub mod sepa_xml;
+pub mod render;
+pub mod spec;
+pub mod validator;
+
+use std::borrow::Cow;
 use std::fmt::Debug;
-use std::path::Path;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-
-use arqoii::types::QoiHeader;
+use std::sync::Arc;
 
 #[cfg(feature = "cli")]
 use clap::{builder::PossibleValue, ValueEnum};
 
-use image::ImageBuffer;
-use image::Luma;
-use qrcode::render::Pixel;
 use qrcode::QrCode;
 
+use crate::render::{QrCodeInfo, RenderOptions};
+use crate::validator::{EpcStandardValidator, Validator};
+
 #[derive(Clone)]
 #[non_exhaustive]
 pub enum ImageFormat {
@@ -26,6 +39,7 @@ impl Debug for ImageFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ImageFormat::ImageFormat(format) => write!(f, "{format:?}"),
+            #[cfg(feature = "qoi")]
             ImageFormat::Qoi => write!(f, "Qoi"),
         }
     }
@@ -40,11 +54,7 @@ impl Default for ImageFormat {
 #[cfg(feature = "cli")]
 impl ValueEnum for ImageFormat {
     fn value_variants<'a>() -> &'a [Self] {
-        &[
-            Self::Qoi,
-            Self::ImageFormat(image::ImageFormat::Png),
-            Self::ImageFormat(image::ImageFormat::Jpeg),
-        ]
+        Self::SUPPORTED
     }
 
     fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
@@ -54,92 +64,74 @@ impl ValueEnum for ImageFormat {
 }
 
 impl ImageFormat {
+    /// The formats supported with the enabled features
+    pub const SUPPORTED: &'static [Self] = &[
+        #[cfg(feature = "qoi")]
+        Self::Qoi,
+        Self::ImageFormat(image::ImageFormat::Png),
+        Self::ImageFormat(image::ImageFormat::Jpeg),
+        Self::ImageFormat(image::ImageFormat::Gif),
+        Self::ImageFormat(image::ImageFormat::Bmp),
+    ];
+
+    /// The file extensions of the [supported formats](ImageFormat::SUPPORTED), e.g. `["png", "jpg"]`
+    pub fn supported_extensions() -> Vec<&'static str> {
+        Self::SUPPORTED.iter().map(Self::extension).collect()
+    }
+
     pub fn png() -> Self {
         Self::ImageFormat(image::ImageFormat::Png)
     }
 
-    #[cfg(feature = "qoi")]
-    pub fn qoi() -> Self {
-        Self::Qoi
+    pub fn jpeg() -> Self {
+        Self::ImageFormat(image::ImageFormat::Jpeg)
     }
-}
 
-struct Image {
-    buffer: ImageBuffer<Luma<u8>, Vec<u8>>,
-}
-
-impl Image {
-    pub fn save(&self, format: ImageFormat, file_path: &Path) -> Result<(), GenerationError> {
-        match format {
-            ImageFormat::ImageFormat(format) => {
-                self.buffer.save_with_format(file_path, format)?;
-            }
-            ImageFormat::Qoi => {
-                let data = arqoii::QoiEncoder::new(
-                    QoiHeader::new(
-                        self.buffer.width(),
-                        self.buffer.height(),
-                        arqoii::types::QoiChannels::Rgb,
-                        arqoii::types::QoiColorSpace::SRgbWithLinearAlpha,
-                    ),
-                    self.buffer.pixels().map(|px| arqoii::Pixel {
-                        r: px.0[0],
-                        g: px.0[0],
-                        b: px.0[0],
-                        a: 255,
-                    }),
-                )
-                .collect::<Vec<_>>();
-                std::fs::write(file_path, data)?;
-            }
-        }
-        Ok(())
+    pub fn gif() -> Self {
+        Self::ImageFormat(image::ImageFormat::Gif)
     }
-    pub fn save_guess_format(&self, file_path: &Path) -> Result<(), GenerationError> {
-        if cfg!(feature = "qoi") && file_path.extension().is_some_and(|ext| ext == "qoi") {
-            self.save(ImageFormat::Qoi, file_path)
-        } else {
-            self.buffer.save(file_path)?;
-            Ok(())
-        }
-    }
-}
 
-#[derive(Debug, Clone, Copy)]
-struct Px(Luma<u8>);
-
-struct Canvas(Px, Image);
-
-impl Pixel for Px {
-    type Image = Image;
-
-    type Canvas = Canvas;
-
-    fn default_color(color: qrcode::Color) -> Self {
-        Self(Luma([color.select(0, 255)]))
+    /// Uncompressed bitmaps, e.g. for legacy label design applications that only import BMP
+    ///
+    /// Like PNG the format is lossless, so scanning is unaffected, but the files are much larger.
+    pub fn bmp() -> Self {
+        Self::ImageFormat(image::ImageFormat::Bmp)
     }
-}
-
-impl qrcode::render::Canvas for Canvas {
-    type Pixel = Px;
 
-    type Image = <Px as Pixel>::Image;
+    #[cfg(feature = "qoi")]
+    pub fn qoi() -> Self {
+        Self::Qoi
+    }
 
-    fn new(width: u32, height: u32, dark_pixel: Self::Pixel, light_pixel: Self::Pixel) -> Self {
-        Self(
-            dark_pixel,
-            Image {
-                buffer: ImageBuffer::from_pixel(width, height, light_pixel.0),
-            },
-        )
+    /// The file extension used for files of this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::ImageFormat(format) => format.extensions_str()[0],
+            #[cfg(feature = "qoi")]
+            ImageFormat::Qoi => "qoi",
+        }
     }
 
-    fn draw_dark_pixel(&mut self, x: u32, y: u32) {
-        self.1.buffer.put_pixel(x, y, self.0 .0)
+    /// The MIME type of this format, e.g. `image/png` for a `Content-Type` header
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::ImageFormat(format) => format.to_mime_type(),
+            #[cfg(feature = "qoi")]
+            ImageFormat::Qoi => "image/x-qoi",
+        }
     }
 
-    fn into_image(self) -> Self::Image {
-        self.1
+    /// The format with the given MIME type, e.g. for content negotiation with an `Accept` header
+    ///
+    /// The comparison ignores case and parameters like `;q=0.8`. Only the [supported formats](ImageFormat::SUPPORTED)
+    /// are found, e.g. `image/x-qoi` only with the `qoi` feature. SVG is not an image format here,
+    /// see [`EpcQr::to_svg`] for `image/svg+xml`, and WebP is not supported.
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        let mime = mime.split(';').next().unwrap_or_default().trim();
+        Self::SUPPORTED
+            .iter()
+            .find(|format| format.mime_type().eq_ignore_ascii_case(mime))
+            .cloned()
     }
 }
 
@@ -151,38 +143,201 @@ pub enum GenerationError {
     ImageError(#[from] image::error::ImageError),
     #[error("{0}")]
     Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Jpeg(#[from] jpeg_encoder::EncodingError),
+    #[cfg(feature = "zip")]
+    #[error("{0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[cfg(feature = "clipboard")]
+    #[error("Couldn't read the clipboard: {0}")]
+    Clipboard(#[from] arboard::Error),
+    #[cfg(feature = "clipboard")]
+    #[error("Couldn't find a valid IBAN in the clipboard")]
+    NoIbanInClipboard,
     #[error("{0:?}")]
     InvalidEpcCode(#[from] InvalidEpcCode),
+    #[error("{0}")]
+    InvalidPayload(#[from] InvalidPayload),
+    #[error("{0}")]
+    InvalidAmount(#[from] InvalidAmount),
+    #[error("Unclosed placeholder in file name template '{0}'")]
+    InvalidTemplate(String),
+    #[error("Unknown placeholder '{0}' in file name template")]
+    UnknownPlaceholder(String),
+    #[error("Invalid CSV in line {line}: {message}")]
+    InvalidCsv { line: usize, message: &'static str },
+    #[error("Failed to generate line {line}: {source}")]
+    Row {
+        line: usize,
+        source: Box<GenerationError>,
+    },
+    #[error("The payload doesn't fit into a QR-Code of version {version:?}")]
+    PayloadTooLargeForVersion { version: qrcode::Version },
+    #[error("The payload needs QR-Code version {got}, but at most version {max} is allowed")]
+    VersionTooHigh { got: i16, max: u8 },
+    #[error(
+        "The generated image doesn't match the encoded data, {mismatched_modules} modules differ"
+    )]
+    VerificationFailed { mismatched_modules: usize },
+    #[cfg(feature = "decode")]
+    #[error("The generated image doesn't decode to the encoded payload, {0}")]
+    DecodingFailed(String),
+    #[error("Rejected in strict compatibility mode, {0}")]
+    StrictCompatibility(&'static str),
+    #[error("Invalid URL '{0}', expected an absolute http or https URL")]
+    InvalidUrl(String),
+    #[error("Invalid color '{input}', expected #rrggbb, rrggbb or #rgb")]
+    InvalidColor { input: String },
+    #[error("Invalid frame '{input}', expected <width>:<color>, e.g. 4:000000")]
+    InvalidFrame { input: String },
+    #[error("The color {color:?} doesn't contrast enough with the light color to be scanned reliably")]
+    InsufficientContrast { color: [u8; 3] },
+    #[error(
+        "The image of {width}x{height} pixels doesn't fit on the canvas of {canvas_size}x{canvas_size} pixels"
+    )]
+    CanvasTooSmall {
+        width: u32,
+        height: u32,
+        canvas_size: u32,
+    },
 }
 
+/// An EPC QR-Code borrowing its text fields, see [`EpcQr`]
+pub type EpcQrRef<'a> = EpcQr<&'a str>;
+
+/// An EPC QR-Code
+///
+/// The text fields are generic over `S`, which defaults to owned [`String`]s.
+/// Use [`EpcQrRef`] to build codes from borrowed data without allocating a `String` per field.
+///
+/// `EpcQr` is `Send` and `Sync` whenever `S` is, so codes can be shared with and rendered on other threads.
 #[derive(Debug, Clone)]
-pub struct EpcQr {
+pub struct EpcQr<S = String> {
+    /// Explicitly selected version, inferred from the presence of the BIC if unset
+    version: Option<EpcVersion>,
     character_set: CharacterSet,
+    line_ending: LineEnding,
+    /// Collapse whitespace runs in the name and info fields
+    normalize_whitespace: bool,
+    /// Compose the text fields to Unicode NFC
+    normalize_unicode: bool,
+    /// Reject codes banks are known to have problems with, see [`EpcQr::with_strict_compatibility`]
+    strict_compatibility: bool,
+    /// Keep the trailing unset fields in the payload instead of omitting them
+    emit_trailing_empty: bool,
+    /// Keep the empty remittance lines in the payload while no remittance is set
+    empty_remittance: bool,
+    /// Move the overflow of a too long remittance text into the unset info
+    split_long_remittance: bool,
     /// AT-23 BIC of Beneficiary Bank (8/11 characters)
     /// Mandatory in Version 1
     /// Optional in Version 2 inside the EEA
-    bic: Option<String>,
+    bic: Option<S>,
     /// AT-21 Name of Beneficiary (max. 70. characters)
-    beneficiary_name: String,
+    beneficiary_name: S,
     /// AT-20 Account # of Beneficiary (max 34. characters)
     /// Only IBAN is allowed
-    beneficiary_account: String,
+    beneficiary_account: S,
     // AT-04 Amount in Euro
-    // Must be between 0.01 and 999999999.99 inclusive
+    // Must be between Amount::MIN and Amount::MAX inclusive
     amount: Option<Amount>,
     /// AT-44 Purpose of Credit Transfer (max. 4 characters)
-    purpose: Option<String>,
-    remittance: Option<Remittance>,
+    purpose: Option<S>,
+    remittance: Option<Remittance<S>>,
     /// Beneficiary to originator Information (max. 70 characters)
-    info: Option<String>,
+    info: Option<S>,
+    /// Additional lines after the info, e.g. for national scheme variants
+    extra_fields: Vec<S>,
+    /// The originator's own reference, metadata that is not part of the payload
+    internal_reference: Option<S>,
+    /// The rules the fields are validated with, [`EpcStandardValidator`] if unset
+    validator: Option<Arc<dyn Validator>>,
+}
+
+/// All fields of an EPC QR-Code at once, see [`EpcQr::from_parts`]
+#[derive(Debug, Clone)]
+pub struct FromParts<S = String> {
+    /// Name of the beneficiary (max. 70 characters)
+    pub name: S,
+    /// IBAN of the beneficiary (max. 34 characters)
+    pub account: S,
+    /// BIC of the beneficiary bank (8/11 characters)
+    pub bic: Option<S>,
+    pub amount: Option<Amount>,
+    /// Purpose of the credit transfer (max. 4 characters)
+    pub purpose: Option<S>,
+    pub remittance: Option<Remittance<S>>,
+    /// Beneficiary to originator information (max. 70 characters)
+    pub info: Option<S>,
+    pub charset: CharacterSet,
 }
 
 impl EpcQr {
-    const MAX_LENGTH_BYTES: usize = 331;
+    /// A valid, fully populated sample code, e.g. for documentation, tests or trying out rendering options
+    ///
+    /// It uses the well-known example IBAN `DE89370400440532013000`, the ISO 11649 example creditor reference
+    /// `RF18539007547034` and otherwise made-up data, so it doesn't belong to any real account.
+    pub fn example() -> Self {
+        EpcQr::new(
+            "Max Mustermann".to_owned(),
+            "DE89370400440532013000".to_owned(),
+        )
+        .with_bic(Some("COBADEFFXXX".to_owned()))
+        .with_amount(Some(Amount { euro: 12, cent: 50 }))
+        .with_purpose(Some("GDDS".to_owned()))
+        .with_remittance(Some(Remittance::Reference("RF18539007547034".to_owned())))
+        .with_info(Some("Sample EPC QR-Code".to_owned()))
+    }
+
+    /// The default file name template, see [`EpcQr::file_name_from_template`]
+    pub const DEFAULT_FILE_NAME_TEMPLATE: &'static str =
+        "epc-{bic-}{account}{-reference}-qr-code.{ext}";
+
+    /// The default file name template when generating codes for multiple amounts,
+    /// see [`EpcQr::with_amounts`]
+    pub const DEFAULT_AMOUNTS_FILE_NAME_TEMPLATE: &'static str =
+        "epc-{bic-}{account}{-reference}{-amount}-qr-code.{ext}";
+
+    /// Set a structured reference and an unstructured text together, for national extensions accepting both
+    ///
+    /// The payload has a single remittance field, so both are combined into the unstructured text
+    /// as the reference, a single space and the text, e.g. `RF18539007547034 Invoice 42`.
+    /// The combined text is subject to the 140 character limit of [`Remittance::Text`],
+    /// which [`EpcQr::validate`] reports as [`FieldError::InvalidTextLength`].
+    ///
+    /// Only use this if the receiving banks are known to support it,
+    /// otherwise prefer [`EpcQr::with_remittance`] with a single [`Remittance`].
+    #[must_use]
+    pub fn with_combined_remittance(self, reference: &str, text: &str) -> Self {
+        self.with_remittance(Some(Remittance::Text(format!("{reference} {text}"))))
+    }
 
-    pub fn new(beneficiary_name: String, beneficiary_account: String) -> Self {
+    /// Render a neutral gray stand-in shaped like a QR-Code, e.g. for UIs while the payment data is still being entered
+    ///
+    /// The image is a square of `size` pixels like a code rendered with [`RenderOptions::with_canvas_size`],
+    /// so it can be swapped for the real code without a layout shift. Sizes too small for 1 pixel per module,
+    /// including the quiet zone and frame, are raised to that minimum. The quiet zone, frame and light color
+    /// of the options are used. **The placeholder encodes nothing and can't be scanned:**
+    /// only its finder patterns are real, the other modules are a fixed pseudo-random pattern.
+    pub fn placeholder_image(size: u32, options: &RenderOptions) -> image::DynamicImage {
+        image::DynamicImage::ImageRgba8(options.render_placeholder(size))
+    }
+}
+
+impl<S: AsRef<str>> EpcQr<S> {
+    /// Create a code for a transfer to `beneficiary_account`, which is written into the payload
+    /// in the electronic format without whitespace and in uppercase
+    pub fn new(beneficiary_name: S, beneficiary_account: S) -> Self {
         Self {
+            version: None,
             character_set: CharacterSet::Utf8,
+            line_ending: LineEnding::Lf,
+            normalize_whitespace: false,
+            normalize_unicode: false,
+            strict_compatibility: false,
+            emit_trailing_empty: false,
+            empty_remittance: false,
+            split_long_remittance: false,
             bic: None,
             beneficiary_name,
             beneficiary_account,
@@ -190,195 +345,1373 @@ impl EpcQr {
             purpose: None,
             remittance: None,
             info: None,
+            extra_fields: Vec::new(),
+            internal_reference: None,
+            validator: None,
+        }
+    }
+
+    /// Create a code like [`EpcQr::new`], but validate the beneficiary name and IBAN right away
+    ///
+    /// Fails with [`InvalidEpcCode::InvalidFieldLength`] if the name is empty or too long,
+    /// or if the IBAN is malformed or its check digits don't match.
+    /// The optional fields set afterwards are only validated by [`EpcQr::validate`].
+    pub fn try_new(beneficiary_name: S, beneficiary_account: S) -> Result<Self, InvalidEpcCode> {
+        let code = Self::new(beneficiary_name, beneficiary_account);
+        code.validate_fields()?;
+        Ok(code)
+    }
+
+    /// Create a code with all fields at once, e.g. from deserialized data
+    ///
+    /// The remaining settings like the EPC version use their defaults and can be changed with the builder methods.
+    pub fn from_parts(parts: FromParts<S>) -> Self {
+        Self::new(parts.name, parts.account)
+            .with_character_set(parts.charset)
+            .with_bic(parts.bic)
+            .with_amount(parts.amount)
+            .with_purpose(parts.purpose)
+            .with_remittance(parts.remittance)
+            .with_info(parts.info)
+    }
+
+    /// Select the EPC QR-Code version explicitly
+    ///
+    /// When unset the version is inferred, Version 1 is used when a BIC is present and Version 2 otherwise.
+    /// An explicit version takes precedence over the inference: with Version 2 a set BIC is still encoded,
+    /// as the BIC is optional but not forbidden there, while the payload declares version `002`.
+    #[must_use]
+    pub fn with_epc_version(mut self, version: Option<EpcVersion>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// The EPC QR-Code version that will be encoded, either explicitly selected or inferred
+    pub fn epc_version(&self) -> EpcVersion {
+        self.version.unwrap_or(if self.bic.is_some() {
+            EpcVersion::V1
+        } else {
+            EpcVersion::V2
+        })
+    }
+
+    /// The name of the beneficiary, as set, without normalizing whitespace
+    pub fn beneficiary_name(&self) -> &str {
+        self.beneficiary_name.as_ref()
+    }
+
+    /// The account of the beneficiary, i.e. the IBAN as set
+    pub fn beneficiary_account(&self) -> &str {
+        self.beneficiary_account.as_ref()
+    }
+
+    /// The BIC of the beneficiary's bank, if set
+    pub fn bic(&self) -> Option<&str> {
+        self.bic.as_ref().map(AsRef::as_ref)
+    }
+
+    /// The amount in euro, if set
+    pub fn amount(&self) -> Option<&Amount> {
+        self.amount.as_ref()
+    }
+
+    /// The purpose code, if set
+    pub fn purpose(&self) -> Option<&str> {
+        self.purpose.as_ref().map(AsRef::as_ref)
+    }
+
+    /// The remittance reference or text, if set
+    pub fn remittance(&self) -> Option<&Remittance<S>> {
+        self.remittance.as_ref()
+    }
+
+    /// The beneficiary to originator information, as set, without normalizing whitespace
+    pub fn info(&self) -> Option<&str> {
+        self.info.as_ref().map(AsRef::as_ref)
+    }
+
+    /// The originator's own reference, see [`EpcQr::with_internal_reference`]
+    pub fn internal_reference(&self) -> Option<&str> {
+        self.internal_reference.as_ref().map(AsRef::as_ref)
+    }
+
+    /// The character set the payload is encoded in
+    pub fn character_set(&self) -> &CharacterSet {
+        &self.character_set
+    }
+
+    /// Convert into an explicit EPC QR-Code version, keeping all fields
+    ///
+    /// Converting to Version 1 fails without a BIC, with the same error [`EpcQr::validate_verbose`]
+    /// reports as [`FieldError::MissingBic`]. Converting to Version 2 keeps the BIC, as it's optional there.
+    pub fn to_version(self, version: EpcVersion) -> Result<Self, InvalidEpcCode> {
+        if version == EpcVersion::V1 && self.bic.is_none() {
+            return Err(InvalidEpcCode::from_field_errors(&[FieldError::MissingBic])
+                .expect("a missing BIC is an error"));
+        }
+        Ok(self.with_epc_version(Some(version)))
+    }
+
+    /// Whether the BIC is mandatory for the version that will be encoded
+    ///
+    /// This is the case for Version 1, in Version 2 the BIC is optional inside the EEA.
+    /// As there are no checks whether the beneficiary is inside the EEA, Version 2 never requires the BIC.
+    pub fn bic_required(&self) -> bool {
+        match self.epc_version() {
+            EpcVersion::V1 => true,
+            EpcVersion::V2 => false,
+        }
+    }
+
+    /// Select the character set used to encode the payload, defaults to [`CharacterSet::Utf8`]
+    ///
+    /// Generating a code fails with [`InvalidEpcCode::UnencodableCharacter`] if a field contains
+    /// a character the character set can't represent. Use [`EpcQr::try_with_character_set`] to check this right away.
+    #[must_use]
+    pub fn with_character_set(mut self, character_set: CharacterSet) -> Self {
+        self.character_set = character_set;
+        self
+    }
+
+    /// Select the character set like [`EpcQr::with_character_set`],
+    /// but fail if a field set so far contains a character it can't represent
+    ///
+    /// Fails with [`InvalidEpcCode::UnencodableCharacter`] for the first such character,
+    /// e.g. when switching from UTF-8 to ISO 8859-1 with a `€` in the info.
+    /// Fields set afterwards are only checked when generating the code.
+    pub fn try_with_character_set(
+        self,
+        character_set: CharacterSet,
+    ) -> Result<Self, InvalidEpcCode> {
+        let code = self.with_character_set(character_set);
+        code.check_encodable()?;
+        Ok(code)
+    }
+
+    /// Fail if the payload contains a character the character set can't represent
+    fn check_encodable(&self) -> Result<(), InvalidEpcCode> {
+        match encoding::encode(&self.character_set, &self.to_string()) {
+            Ok(_) => Ok(()),
+            Err(character) => Err(InvalidEpcCode::UnencodableCharacter {
+                character,
+                character_set: self.character_set.clone(),
+            }),
+        }
+    }
+
+    /// Select the separator between the fields of the payload, defaults to [`LineEnding::Lf`]
+    ///
+    /// [`LineEnding::CrLf`] needs an extra byte per line, which counts towards the 331 byte limit.
+    #[must_use]
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Collapse runs of whitespace in the beneficiary name and info into single spaces
+    /// and remove leading and trailing whitespace, before validation and encoding
+    ///
+    /// Banks often normalize whitespace themselves, so `"John    Doe"` may otherwise be rejected or altered.
+    /// Disabled by default, so that names are encoded exactly as given.
+    #[must_use]
+    pub fn with_normalize_whitespace(mut self, normalize_whitespace: bool) -> Self {
+        self.normalize_whitespace = normalize_whitespace;
+        self
+    }
+
+    /// Compose the beneficiary name, remittance and info to Unicode NFC, before validation and encoding
+    ///
+    /// Text copied from other sources may contain decomposed characters, e.g. `e` followed by a combining accent
+    /// instead of `é`, which count as more characters and bytes towards the length limits
+    /// and encode visually identical names differently. Disabled by default, so that fields are encoded exactly as given.
+    #[cfg(feature = "unicode-normalization")]
+    #[must_use]
+    pub fn with_normalize_unicode(mut self, normalize_unicode: bool) -> Self {
+        self.normalize_unicode = normalize_unicode;
+        self
+    }
+
+    /// Reject codes with pitfalls some banks are known to have problems with, for maximal compatibility
+    ///
+    /// Disabled by default. When enabled:
+    /// - the BIC is required, even for Version 2, reported as [`FieldError::MissingBic`]
+    /// - the IBAN must be from a country of the SEPA scheme, reported as [`FieldError::NonSepaIban`]
+    /// - generating images in a lossy format like JPEG fails with [`GenerationError::StrictCompatibility`]
+    /// - generating images with a quiet zone narrower than 4 modules on any side fails the same way
+    /// - amounts encoded with a single decimal, i.e. whole tens of cents like `EUR12.5` for 12.50 euro,
+    ///   are reported as [`FieldError::SingleDecimalAmount`]
+    #[must_use]
+    pub fn with_strict_compatibility(mut self, strict_compatibility: bool) -> Self {
+        self.strict_compatibility = strict_compatibility;
+        self
+    }
+
+    /// Keep the trailing unset fields as empty lines, so the payload always has all twelve lines
+    ///
+    /// By default trailing unset fields are omitted, which the specification allows and which saves a few bytes.
+    /// Some readers expect all lines to be present, emitting them maximizes compatibility with those.
+    /// Unset fields before the last set one are always emitted as empty lines.
+    #[must_use]
+    pub fn with_emit_trailing_empty(mut self, emit_trailing_empty: bool) -> Self {
+        self.emit_trailing_empty = emit_trailing_empty;
+        self
+    }
+
+    /// Move the overflow of a remittance text longer than 140 characters into the info field, if that is unset
+    ///
+    /// The text keeps its first 140 characters and the info holds the rest, starting with the marker `...`.
+    /// Validation fails as usual if the overflow still doesn't fit into the 70 characters of the info.
+    ///
+    /// **This changes the meaning of the overflow:** the info is beneficiary to originator information
+    /// that banks may not show as part of the remittance, so only enable it if that is acceptable.
+    #[must_use]
+    pub fn with_split_long_remittance(mut self, split_long_remittance: bool) -> Self {
+        self.split_long_remittance = split_long_remittance;
+        self
+    }
+
+    /// The remittance text and info to encode if a too long text is split, see [`EpcQr::with_split_long_remittance`]
+    fn split_remittance(&self) -> Option<(String, String)> {
+        if !self.split_long_remittance || self.info.is_some() {
+            return None;
+        }
+        let Some(Remittance::Text(text)) = self.normalized_remittance() else {
+            return None;
+        };
+        let (split, _) = text.char_indices().nth(Remittance::MAX_TEXT_LEN)?;
+        Some((
+            text[..split].to_owned(),
+            format!("{REMITTANCE_CONTINUATION}{}", &text[split..]),
+        ))
+    }
+
+    /// Apply the enabled normalizations to the beneficiary name or info
+    fn normalized<'a>(&self, field: &'a str) -> Cow<'a, str> {
+        let field = if self.normalize_whitespace {
+            Cow::Owned(field.split_whitespace().collect::<Vec<_>>().join(" "))
+        } else {
+            Cow::Borrowed(field)
+        };
+        self.unicode_normalized(field)
+    }
+
+    /// Compose `field` to Unicode NFC, if enabled
+    fn unicode_normalized<'a>(&self, field: Cow<'a, str>) -> Cow<'a, str> {
+        #[cfg(feature = "unicode-normalization")]
+        if self.normalize_unicode && !unicode_normalization::is_nfc(&field) {
+            use unicode_normalization::UnicodeNormalization;
+            return Cow::Owned(field.nfc().collect());
+        }
+        field
+    }
+
+    /// The beneficiary account as written into the payload, without whitespace and in uppercase
+    fn normalized_account(&self) -> Cow<'_, str> {
+        let account = self.beneficiary_account.as_ref();
+        if account
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_lowercase())
+        {
+            Cow::Owned(
+                account
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect::<String>()
+                    .to_uppercase(),
+            )
+        } else {
+            Cow::Borrowed(account)
         }
     }
 
-    pub fn with_bic(mut self, bic: Option<String>) -> Self {
+    /// The purpose as written into the payload, trimmed and in uppercase
+    fn normalized_purpose(&self) -> Option<Cow<'_, str>> {
+        self.purpose.as_ref().map(|purpose| {
+            let purpose = purpose.as_ref().trim();
+            if purpose.chars().any(char::is_lowercase) {
+                Cow::Owned(purpose.to_uppercase())
+            } else {
+                Cow::Borrowed(purpose)
+            }
+        })
+    }
+
+    /// The remittance with the enabled Unicode normalization applied
+    pub(crate) fn normalized_remittance(&self) -> Option<Remittance<Cow<'_, str>>> {
+        self.remittance.as_ref().map(|remittance| match remittance {
+            Remittance::Reference(reference) => {
+                Remittance::Reference(self.unicode_normalized(Cow::Borrowed(reference.as_ref())))
+            }
+            Remittance::Text(text) => {
+                Remittance::Text(self.unicode_normalized(Cow::Borrowed(text.as_ref())))
+            }
+        })
+    }
+
+    #[must_use]
+    pub fn with_bic(mut self, bic: Option<S>) -> Self {
         self.bic = bic;
         self
     }
 
+    #[must_use]
     pub fn with_amount(mut self, amount: Option<Amount>) -> Self {
         self.amount = amount;
         self
     }
 
-    pub fn with_purpose(mut self, purpose: Option<String>) -> Self {
+    /// Borrow the text fields, e.g. to generate the code without cloning it
+    pub fn as_borrowed(&self) -> EpcQrRef<'_> {
+        EpcQr {
+            version: self.version,
+            character_set: self.character_set.clone(),
+            line_ending: self.line_ending,
+            normalize_whitespace: self.normalize_whitespace,
+            normalize_unicode: self.normalize_unicode,
+            strict_compatibility: self.strict_compatibility,
+            emit_trailing_empty: self.emit_trailing_empty,
+            empty_remittance: self.empty_remittance,
+            split_long_remittance: self.split_long_remittance,
+            bic: self.bic.as_ref().map(AsRef::as_ref),
+            beneficiary_name: self.beneficiary_name.as_ref(),
+            beneficiary_account: self.beneficiary_account.as_ref(),
+            amount: self.amount.clone(),
+            purpose: self.purpose.as_ref().map(AsRef::as_ref),
+            remittance: self.remittance.as_ref().map(|remittance| match remittance {
+                Remittance::Reference(reference) => Remittance::Reference(reference.as_ref()),
+                Remittance::Text(text) => Remittance::Text(text.as_ref()),
+            }),
+            info: self.info.as_ref().map(AsRef::as_ref),
+            extra_fields: self.extra_fields.iter().map(AsRef::as_ref).collect(),
+            internal_reference: self.internal_reference.as_ref().map(AsRef::as_ref),
+            validator: self.validator.clone(),
+        }
+    }
+
+    /// Convert into a code owning its text fields
+    pub fn into_owned(self) -> EpcQr {
+        EpcQr {
+            version: self.version,
+            character_set: self.character_set,
+            line_ending: self.line_ending,
+            normalize_whitespace: self.normalize_whitespace,
+            normalize_unicode: self.normalize_unicode,
+            strict_compatibility: self.strict_compatibility,
+            emit_trailing_empty: self.emit_trailing_empty,
+            empty_remittance: self.empty_remittance,
+            split_long_remittance: self.split_long_remittance,
+            bic: self.bic.map(|bic| bic.as_ref().to_string()),
+            beneficiary_name: self.beneficiary_name.as_ref().to_string(),
+            beneficiary_account: self.beneficiary_account.as_ref().to_string(),
+            amount: self.amount,
+            purpose: self.purpose.map(|purpose| purpose.as_ref().to_string()),
+            remittance: self.remittance.map(Remittance::into_owned),
+            info: self.info.map(|info| info.as_ref().to_string()),
+            extra_fields: self
+                .extra_fields
+                .into_iter()
+                .map(|field| field.as_ref().to_string())
+                .collect(),
+            internal_reference: self
+                .internal_reference
+                .map(|reference| reference.as_ref().to_string()),
+            validator: self.validator,
+        }
+    }
+
+    /// Create one code per amount sharing all other fields, e.g. for tiered donation codes
+    ///
+    /// Each code is validated on its own when generated.
+    #[must_use]
+    pub fn with_amounts(&self, amounts: &[Amount]) -> Vec<Self>
+    where
+        S: Clone,
+    {
+        amounts
+            .iter()
+            .map(|amount| self.clone().with_amount(Some(amount.clone())))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn with_purpose(mut self, purpose: Option<S>) -> Self {
         self.purpose = purpose;
         self
     }
 
-    pub fn with_remittance(mut self, remittance: Option<Remittance>) -> Self {
+    /// Set the remittance, `None` leaves it unset
+    ///
+    /// This replaces a previous [`EpcQr::with_empty_remittance`],
+    /// so without a remittance the empty remittance lines are trimmed again if nothing follows them.
+    #[must_use]
+    pub fn with_remittance(mut self, remittance: Option<Remittance<S>>) -> Self {
         self.remittance = remittance;
+        self.empty_remittance = false;
         self
     }
 
-    pub fn with_info(mut self, info: Option<String>) -> Self {
+    /// Leave the remittance unset, but keep its lines in the payload even if nothing follows them
+    ///
+    /// An absent remittance, as set with `with_remittance(None)`, is trimmed together with the other
+    /// trailing unset fields, so the payload may end before the reference and text lines.
+    /// A present but empty remittance always includes both lines empty, for readers expecting them.
+    /// Either way the payload encodes no remittance, only its number of lines differs.
+    #[must_use]
+    pub fn with_empty_remittance(mut self) -> Self {
+        self.remittance = None;
+        self.empty_remittance = true;
+        self
+    }
+
+    #[must_use]
+    pub fn with_info(mut self, info: Option<S>) -> Self {
         self.info = info;
         self
     }
 
-    fn validate(&self) -> Result<(), InvalidEpcCode> {
-        let invalid_bic = self
-            .bic
-            .as_ref()
-            .is_some_and(|bic| ![8, 11].contains(&bic.chars().count()));
-        let invalid_name = !(1..=70).contains(&self.beneficiary_name.chars().count());
-        let invalid_iban = !(1..=34).contains(&self.beneficiary_account.chars().count());
-        let invalid_amount = self.amount.as_ref().is_some_and(|amount| {
-            999999999 < amount.euro || 99 < amount.cent || (amount.euro == 0 && amount.cent == 0)
-        });
-        let invalid_purpose = self
-            .purpose
-            .as_ref()
-            .is_some_and(|purpose| !(1..=4).contains(&purpose.chars().count()));
-        let invalid_remittance =
-            self.remittance
-                .as_ref()
-                .is_some_and(|remittance| match remittance {
-                    Remittance::Reference(reference) => {
-                        !(1..=35).contains(&reference.chars().count())
-                    }
-                    Remittance::Text(text) => !(1..=140).contains(&text.chars().count()),
+    /// Append additional lines after the info, as some national guidelines define optional trailing elements
+    ///
+    /// Each field must not contain a line break and counts towards the 331 byte limit.
+    /// Empty by default, which produces the standard payload.
+    #[must_use]
+    pub fn with_extra_fields(mut self, extra_fields: Vec<S>) -> Self {
+        self.extra_fields = extra_fields;
+        self
+    }
+
+    /// Attach the originator's own reference, e.g. an invoice or record id to reconcile generated files
+    ///
+    /// **This is metadata only:** it is never encoded into the payload, validated or compared for equality.
+    /// It is available as `{internal_reference}` in [`EpcQr::file_name_from_template`]
+    /// and printed by the CLI, so batch jobs can tell which file belongs to which record.
+    #[must_use]
+    pub fn with_internal_reference(mut self, internal_reference: Option<S>) -> Self {
+        self.internal_reference = internal_reference;
+        self
+    }
+
+    /// Validate the fields with a custom [`Validator`] instead of the [`EpcStandardValidator`],
+    /// e.g. to enforce stricter rules of a specific bank
+    #[must_use]
+    pub fn with_validator(mut self, validator: Box<dyn Validator>) -> Self {
+        self.validator = Some(Arc::from(validator));
+        self
+    }
+
+    /// Validate all fields, collecting one [`FieldError`] per problem found
+    ///
+    /// The fields are checked by the [`Validator`] set with [`EpcQr::with_validator`],
+    /// or the [`EpcStandardValidator`] by default. An empty list means all fields are valid.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn validate_verbose(&self) -> Vec<FieldError> {
+        let split = self.split_remittance();
+        let code = match &split {
+            Some((text, info)) => self
+                .as_borrowed()
+                .with_remittance(Some(Remittance::Text(text.as_str())))
+                .with_info(Some(info.as_str())),
+            None => self.as_borrowed(),
+        };
+        let mut errors = match &self.validator {
+            Some(validator) => validator.validate(&code),
+            None => EpcStandardValidator.validate(&code),
+        };
+
+        if self.strict_compatibility {
+            if self.bic.is_none() && !errors.contains(&FieldError::MissingBic) {
+                errors.push(FieldError::MissingBic);
+            }
+            let account = self.normalized_account();
+            let country = account.get(..2).unwrap_or_default();
+            if !SEPA_COUNTRIES.contains(&country) {
+                errors.push(FieldError::NonSepaIban {
+                    country: country.to_owned(),
                 });
-        let invalid_info = self
-            .info
-            .as_ref()
-            .is_some_and(|info| !(1..=70).contains(&info.chars().count()));
-
-        if invalid_bic
-            || invalid_name
-            || invalid_iban
-            || invalid_amount
-            || invalid_purpose
-            || invalid_remittance
-            || invalid_info
-        {
-            Err(InvalidEpcCode::InvalidFieldLength {
-                invalid_bic,
-                invalid_name,
-                invalid_iban,
-                invalid_amount,
-                invalid_purpose,
-                invalid_remittance,
-                invalid_info,
-            })
-        } else {
-            Ok(())
+            }
+            if let Some(amount) = self.amount.as_ref().filter(|amount| amount.cent % 10 == 0) {
+                errors.push(FieldError::SingleDecimalAmount {
+                    euro: amount.euro,
+                    cent: amount.cent,
+                });
+            }
         }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            epc_version = ?self.epc_version(),
+            bic_length = self.bic.as_ref().map(|bic| bic.as_ref().chars().count()),
+            name_length = self.beneficiary_name.as_ref().chars().count(),
+            account_length = self.beneficiary_account.as_ref().chars().count(),
+            purpose_length = self.purpose.as_ref().map(|purpose| purpose.as_ref().chars().count()),
+            remittance_length = self
+                .remittance
+                .as_ref()
+                .map(|remittance| remittance.text().chars().count()),
+            info_length = self.info.as_ref().map(|info| info.as_ref().chars().count()),
+            ?errors,
+            "validated fields"
+        );
+
+        errors
     }
 
-    fn data(&self) -> Result<Vec<u8>, InvalidEpcCode> {
+    /// Validate all fields and the size of the payload, bundling the problems into a single error
+    ///
+    /// This is [`EpcQr::validate_fields`] followed by [`EpcQr::validate_size`].
+    pub fn validate(&self) -> Result<(), InvalidEpcCode> {
+        self.validate_fields()?;
+        self.validate_size()
+    }
 
+    /// Validate the rules of the individual fields, bundling the problems into a single error
+    ///
+    /// See [`EpcQr::validate_verbose`] for the individual problems.
+    /// Unlike [`EpcQr::validate`] this doesn't check the size of the whole payload,
+    /// e.g. to give feedback while the fields are still being edited.
+    pub fn validate_fields(&self) -> Result<(), InvalidEpcCode> {
+        match InvalidEpcCode::from_field_errors(&self.validate_verbose()) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that the payload fits into the 331 byte limit, without validating the individual fields
+    ///
+    /// Fields that are valid on their own can still exceed the limit together,
+    /// which fails with [`InvalidEpcCode::CombinedTooLarge`].
+    pub fn validate_size(&self) -> Result<(), InvalidEpcCode> {
+        let payload = self.to_string();
+        let total = encoding::encode(&self.character_set, &payload)
+            .map_or(payload.len(), |data| data.len());
+        if spec::MAX_PAYLOAD_BYTES < total {
+            return Err(InvalidEpcCode::CombinedTooLarge {
+                total,
+                max: spec::MAX_PAYLOAD_BYTES,
+            });
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(character_set = %self.character_set))
+    )]
+    fn data(&self) -> Result<Vec<u8>, InvalidEpcCode> {
+        // this includes the size limit of the encoded payload
         self.validate()?;
 
-        // while the enum lists all character sets for now we just support UTF-8
-        assert!(matches!(self.character_set, CharacterSet::Utf8));
+        let data =
+            encoding::encode(&self.character_set, &self.to_string()).map_err(|character| {
+                InvalidEpcCode::UnencodableCharacter {
+                    character,
+                    character_set: self.character_set.clone(),
+                }
+            })?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            bytes = data.len(),
+            max_bytes = spec::MAX_PAYLOAD_BYTES,
+            "encoded payload"
+        );
+
+        Ok(data)
+    }
+
+    /// Run all checks at once and collect their results, e.g. to answer a form submission
+    ///
+    /// This combines [`EpcQr::validate_verbose`], the size limit of [`EpcQr::validate_size`],
+    /// whether all characters can be encoded in the character set and, with the `security` feature,
+    /// the warnings of `EpcQr::warnings`. Warnings don't make the code invalid.
+    pub fn validation_report(&self) -> ValidationReport {
+        let field_errors = self.validate_verbose();
+        let payload_error = self
+            .validate_size()
+            .and_then(|()| self.check_encodable())
+            .err()
+            .map(|err| err.to_string());
+
+        ValidationReport {
+            valid: field_errors.is_empty() && payload_error.is_none(),
+            field_errors,
+            payload_error,
+            #[cfg(feature = "security")]
+            warnings: self.warnings(),
+        }
+    }
+
+    /// Check for fields that are valid, but suspicious when shown to a human, e.g. for phishing
+    ///
+    /// Warnings don't prevent generating the code, an empty list means nothing suspicious was found.
+    #[cfg(feature = "security")]
+    pub fn warnings(&self) -> Vec<Warning> {
+        use unicode_security::MixedScript;
+
+        let mut warnings = Vec::new();
+        if !self.beneficiary_name.as_ref().is_single_script() {
+            warnings.push(Warning::ConfusableName);
+        }
+        warnings
+    }
+
+    /// Whether all fields are valid, see [`EpcQr::validate`] for the reasons if not
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    /// The payload encoded in the selected character set, as it's stored in the QR-Code
+    ///
+    /// This validates the fields and checks the 331 byte limit.
+    pub fn payload_bytes(&self) -> Result<Vec<u8>, InvalidEpcCode> {
+        self.data()
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn qr_code(&self, options: &RenderOptions) -> Result<QrCode, GenerationError> {
+        let data = self.data()?;
+        let mut ec_levels = options.ec_levels().peekable();
+        let code = loop {
+            let ec_level = ec_levels
+                .next()
+                .expect("the selected level is always tried");
+            let code = match options.version {
+                Some(version) => {
+                    QrCode::with_version(&data, version, ec_level).map_err(|err| match err {
+                        qrcode::types::QrError::DataTooLong => {
+                            GenerationError::PayloadTooLargeForVersion { version }
+                        }
+                        err => err.into(),
+                    })
+                }
+                None => QrCode::with_error_correction_level(&data, ec_level).map_err(Into::into),
+            };
+            // only retry at a lower level if the code doesn't fit, with the last level any error is final
+            match code {
+                Err(GenerationError::PayloadTooLargeForVersion { .. })
+                    if ec_levels.peek().is_some() => {}
+                Ok(code) if ec_levels.peek().is_none() || options.check_version(&code).is_ok() => {
+                    break code
+                }
+                Ok(_) => {}
+                Err(err) => return Err(err),
+            }
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                ?ec_level,
+                "payload doesn't fit, lowering the error correction level"
+            );
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            version = ?code.version(),
+            ec_level = ?code.error_correction_level(),
+            modules = code.width(),
+            "built QR-Code"
+        );
+
+        Ok(code)
+    }
 
-        let data = self.to_string();
+    /// The payload on a single line for logging, e.g. `BCD\n002\n1\nSCT\n...`
+    ///
+    /// Line breaks are escaped as `\n` and `\r` and backslashes as `\\`,
+    /// so the line can't be confused with a payload containing a literal `\n`.
+    pub fn to_debug_line(&self) -> String {
+        self.to_string()
+            .replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    }
 
-        if data.len() <= Self::MAX_LENGTH_BYTES {
-            Ok(data.into_bytes())
+    /// The lines of the payload labeled with their meaning, unset fields are `None`
+    ///
+    /// This lists all twelve lines of the format, e.g. `("service", Some("BCD"))`, `("version", Some("002"))`
+    /// or `("bic", None)`, in the order they appear in the payload.
+    /// The labels are `service`, `version`, `charset`, `identification`, `bic`, `name`, `iban`, `amount`,
+    /// `purpose`, `reference`, `text` and `info`, followed by one `extra` per field set with
+    /// [`EpcQr::with_extra_fields`].
+    pub fn as_fields(&self) -> Vec<(&'static str, Option<String>)> {
+        let version = match self.epc_version() {
+            EpcVersion::V1 => spec::VERSION_1,
+            EpcVersion::V2 => spec::VERSION_2,
+        };
+        let amount = self.amount.as_ref().map(|amount| {
+            if amount.cent % 10 == 0 {
+                format!("EUR{}.{}", amount.euro, amount.cent / 10)
+            } else {
+                format!("EUR{}.{:02}", amount.euro, amount.cent)
+            }
+        });
+        let split = self.split_remittance();
+        let (reference, text) = match self.normalized_remittance() {
+            Some(Remittance::Reference(reference)) => (Some(reference.into_owned()), None),
+            Some(Remittance::Text(text)) => match &split {
+                Some((text, _)) => (None, Some(text.clone())),
+                None => (None, Some(text.into_owned())),
+            },
+            None => (None, None),
+        };
+        let info = match &split {
+            Some((_, info)) => Some(info.as_str()),
+            None => self.info.as_ref().map(AsRef::as_ref),
+        };
+
+        let mut fields = vec![
+            ("service", Some(spec::SERVICE_TAG.to_owned())),
+            ("version", Some(version.to_owned())),
+            (
+                "charset",
+                Some((self.character_set.clone() as u8).to_string()),
+            ),
+            ("identification", Some(spec::SCT_IDENTIFICATION.to_owned())),
+            ("bic", self.bic.as_ref().map(|bic| bic.as_ref().to_owned())),
+            (
+                "name",
+                Some(self.normalized(self.beneficiary_name.as_ref()).into_owned()),
+            ),
+            ("iban", Some(self.normalized_account().into_owned())),
+            ("amount", amount),
+            ("purpose", self.normalized_purpose().map(Cow::into_owned)),
+            ("reference", reference),
+            ("text", text),
+            ("info", info.map(|info| self.normalized(info).into_owned())),
+        ];
+        fields.extend(
+            self.extra_fields
+                .iter()
+                .map(|field| ("extra", Some(field.as_ref().to_owned()))),
+        );
+        fields
+    }
+
+    /// The number of lines of the payload, omitting trailing unset `fields` unless they are to be emitted
+    fn line_count(&self, fields: &[(&'static str, Option<String>)]) -> usize {
+        let used = fields
+            .iter()
+            .rposition(|(_, value)| value.is_some())
+            .map_or(0, |last| last + 1);
+        let used = if self.empty_remittance {
+            let text = fields
+                .iter()
+                .position(|(name, _)| *name == "text")
+                .expect("the text is one of the fixed lines");
+            used.max(text + 1)
+        } else {
+            used
+        };
+        if self.emit_trailing_empty {
+            used.max(spec::MAX_LINES)
         } else {
-            Err(InvalidEpcCode::TooLargeTotal)
+            used
         }
     }
 
+    /// The index of the line holding `field` in the payload as produced by [`EpcQr::to_string`], starting at 0
+    ///
+    /// Unset fields before the last set one keep their empty line and thus have an index,
+    /// while trailing unset fields are omitted from the payload and return `None`,
+    /// unless [`EpcQr::with_emit_trailing_empty`] is enabled. This matches [`EpcQr::as_fields`].
+    pub fn field_line_index(&self, field: EpcField) -> Option<usize> {
+        let index = match field {
+            EpcField::ServiceTag => 0,
+            EpcField::Version => 1,
+            EpcField::CharacterSet => 2,
+            EpcField::Identification => 3,
+            EpcField::Bic => 4,
+            EpcField::BeneficiaryName => 5,
+            EpcField::BeneficiaryAccount => 6,
+            EpcField::Amount => 7,
+            EpcField::Purpose => 8,
+            EpcField::Reference => 9,
+            EpcField::Text => 10,
+            EpcField::Info => 11,
+            EpcField::Extra(extra) => spec::MAX_LINES + extra,
+        };
+        (index < self.line_count(&self.as_fields())).then_some(index)
+    }
+
+    /// A fingerprint of the payload, e.g. as key for caching rendered images
+    ///
+    /// This is the 64 bit FNV-1a hash of the payload as returned by `to_string`.
+    /// It only depends on the payload bytes, so it is stable across platforms and versions of this crate,
+    /// as long as the payload for the same fields doesn't change. Render options are not included.
+    pub fn payload_fingerprint(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x00000100000001b3;
+
+        self.to_string()
+            .bytes()
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    /// The QR-Code version needed to encode this EPC QR-Code
+    ///
+    /// The payload bytes are split into segments of the densest QR-Code mode by the `qrcode` crate,
+    /// e.g. numeric mode for the digits of the IBAN and alphanumeric mode for an uppercase BIC,
+    /// and byte mode for everything else. The payload as a whole is never alphanumeric,
+    /// as the line breaks between the fields are not part of the alphanumeric character set.
+    pub fn qr_version(&self) -> Result<qrcode::Version, GenerationError> {
+        self.qr_version_with_options(&RenderOptions::default())
+    }
+
+    /// The QR-Code version used when generating an image with the given options
+    pub fn qr_version_with_options(
+        &self,
+        options: &RenderOptions,
+    ) -> Result<qrcode::Version, GenerationError> {
+        Ok(self.qr_code(options)?.version())
+    }
+
+    /// The QR-Code version needed at each error correction level, in the order L, M, Q and H
+    ///
+    /// This shows the tradeoff between the size and the robustness of the code,
+    /// pick a level with [`RenderOptions::with_ec_level`].
+    pub fn version_matrix(&self) -> Result<[qrcode::Version; 4], GenerationError> {
+        let version = |ec_level| {
+            self.qr_version_with_options(&RenderOptions::default().with_ec_level(ec_level))
+        };
+        Ok([
+            version(qrcode::EcLevel::L)?,
+            version(qrcode::EcLevel::M)?,
+            version(qrcode::EcLevel::Q)?,
+            version(qrcode::EcLevel::H)?,
+        ])
+    }
+
+    /// Whether the payload fits into a QR-Code of the given version and error correction level
+    ///
+    /// An invalid payload doesn't fit anywhere, so this returns `false` for it.
+    pub fn fits(&self, version: qrcode::Version, ec_level: qrcode::EcLevel) -> bool {
+        self.data()
+            .is_ok_and(|data| QrCode::with_version(data, version, ec_level).is_ok())
+    }
+
+    /// Details about the QR-Code and image generated with the given options
+    pub fn qr_code_info(&self, options: &RenderOptions) -> Result<QrCodeInfo, GenerationError> {
+        let code = self.qr_code(options)?;
+        let (width, height) = options.image_dimensions(&code);
+        Ok(QrCodeInfo {
+            version: code.version(),
+            ec_level: code.error_correction_level(),
+            modules: code.width(),
+            width,
+            height,
+        })
+    }
+
+    /// Estimate the minimal printed edge length in millimeters, including the quiet zone,
+    /// for the code to be scannable from `scan_distance_cm` centimeters away
+    ///
+    /// This uses the common rule of thumb that a QR-Code can be scanned from about ten times its edge length,
+    /// so the minimal module size is taken to be `scan_distance / 10 / modules` with the module count of this code,
+    /// see [`render::min_module_size_mm`]. The result adds the four module quiet zone on each side,
+    /// denser codes get smaller modules, which need a sharper print and camera.
+    ///
+    /// The estimate assumes the error correction level M used for generation, dark modules on a light
+    /// background with good contrast and a typical smartphone camera; treat it as a lower bound.
+    pub fn recommended_min_size_mm(&self, scan_distance_cm: f32) -> Result<f32, GenerationError> {
+        let code = self.qr_code(&RenderOptions::default())?;
+        let module_size_mm = render::min_module_size_mm(scan_distance_cm, code.width());
+        let modules = code.width() + 2 * 4;
+        Ok(module_size_mm * modules as f32)
+    }
+
+    /// The default file name for an image of this code in the given format
+    ///
+    /// The name is built from the BIC (if any), the beneficiary account and the remittance (if any),
+    /// see [`EpcQr::DEFAULT_FILE_NAME_TEMPLATE`].
+    pub fn file_name(&self, format: &ImageFormat) -> String {
+        self.file_name_from_template(EpcQr::DEFAULT_FILE_NAME_TEMPLATE, format)
+            .expect("the default template only uses known placeholders")
+    }
+
+    /// Build a file name by expanding the placeholders in `template`
+    ///
+    /// The available placeholders are
+    /// - `{account}` the beneficiary account
+    /// - `{bic}` the BIC
+    /// - `{amount}` the amount formatted as `#.##`
+    /// - `{reference}` the remittance reference or text
+    /// - `{internal_reference}` the internal reference, see [`EpcQr::with_internal_reference`]
+    /// - `{ext}` the file extension of `format`
+    ///
+    /// Text inside the braces before or after the placeholder name is only included when the field is set,
+    /// e.g. `{bic-}` expands to `BIC-` with a BIC and to nothing without one.
+    /// Path separators and spaces in the expanded values are replaced by underscores.
+    pub fn file_name_from_template(
+        &self,
+        template: &str,
+        format: &ImageFormat,
+    ) -> Result<String, GenerationError> {
+        let mut file_name = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some((literal, placeholder)) = rest.split_once('{') {
+            file_name.push_str(literal);
+            let (placeholder, remainder) = placeholder
+                .split_once('}')
+                .ok_or_else(|| GenerationError::InvalidTemplate(template.to_string()))?;
+            rest = remainder;
+
+            let is_affix = |c: char| !(c.is_ascii_alphabetic() || c == '_');
+            let start = placeholder
+                .find(|c| !is_affix(c))
+                .unwrap_or(placeholder.len());
+            let end = placeholder
+                .rfind(|c| !is_affix(c))
+                .map_or(start, |idx| idx + 1);
+            let (prefix, name, suffix) = (
+                &placeholder[..start],
+                &placeholder[start..end],
+                &placeholder[end..],
+            );
+
+            let value = match name {
+                "account" => Some(self.normalized_account().into_owned()),
+                "bic" => self.bic.as_ref().map(|bic| bic.as_ref().to_string()),
+                "amount" => self.amount.as_ref().map(Amount::to_string),
+                "reference" => self.remittance.as_ref().map(|rem| rem.text().to_string()),
+                "internal_reference" => self.internal_reference().map(str::to_string),
+                "ext" => Some(format.extension().to_string()),
+                _ => return Err(GenerationError::UnknownPlaceholder(name.to_string())),
+            };
+
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                file_name.push_str(prefix);
+                file_name.push_str(&value.replace(['/', '\\', ' '], "_"));
+                file_name.push_str(suffix);
+            }
+        }
+        file_name.push_str(rest);
+
+        Ok(file_name)
+    }
+
+    /// Generate the image and return the encoded bytes instead of writing a file
+    pub fn generate_image_bytes(&self, format: ImageFormat) -> Result<Vec<u8>, GenerationError> {
+        self.generate_image_bytes_with_options(format, &RenderOptions::default())
+    }
+
+    /// Generate the image with the given render options and return the encoded bytes
+    pub fn generate_image_bytes_with_options(
+        &self,
+        format: ImageFormat,
+        options: &RenderOptions,
+    ) -> Result<Vec<u8>, GenerationError> {
+        self.check_rendering(Some(&format), None, options)?;
+        let code = self.qr_code(options)?;
+
+        let image = options.render(&code)?;
+
+        image.encode(format)
+    }
+
+    /// In strict compatibility mode, fail for the image format, guessed from `file_path` if unset,
+    /// or render options banks are known to have problems with
+    fn check_rendering(
+        &self,
+        format: Option<&ImageFormat>,
+        file_path: Option<&Path>,
+        options: &RenderOptions,
+    ) -> Result<(), GenerationError> {
+        if !self.strict_compatibility {
+            return Ok(());
+        }
+        let format = match format {
+            Some(ImageFormat::ImageFormat(format)) => Some(*format),
+            #[cfg(feature = "qoi")]
+            Some(ImageFormat::Qoi) => None,
+            None => file_path.and_then(|path| image::ImageFormat::from_path(path).ok()),
+        };
+        options.check_strict_compatibility(format)
+    }
+
+    /// Render the code into an RGBA image without encoding it, e.g. for live previews in GUI applications
+    ///
+    /// The pixels are stored row by row starting at the top left corner, with four bytes per pixel
+    /// in red, green, blue, alpha order. The image includes the quiet zone.
+    pub fn to_rgba_image(
+        &self,
+        options: &RenderOptions,
+    ) -> Result<image::RgbaImage, GenerationError> {
+        let code = self.qr_code(options)?;
+
+        Ok(options.render(&code)?.into_rgba())
+    }
+
+    /// Render the code as an SVG document, e.g. to embed it into web pages or print it at any size
+    ///
+    /// The document uses the colors, module size, quiet zone and frame of the options,
+    /// its dimensions in pixels match [`EpcQr::qr_code_info`]. All modules are drawn as squares,
+    /// with the dark modules merged into horizontal runs of a single path to keep the document small.
+    pub fn to_svg(&self, options: &RenderOptions) -> Result<String, GenerationError> {
+        let code = self.qr_code(options)?;
+        let caption = if options.svg_caption {
+            self.human_summary()
+        } else {
+            Vec::new()
+        };
+
+        options.render_svg(&code, &caption)
+    }
+
+    /// A short summary of the payment for humans, one line per set field
+    ///
+    /// The lines are the beneficiary, the IBAN, the amount and the remittance reference or text,
+    /// e.g. `Beneficiary: Max Mustermann`, `IBAN: DE89370400440532013000` and `Amount: EUR 12.50`.
+    pub fn human_summary(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "Beneficiary: {}",
+                self.normalized(self.beneficiary_name.as_ref())
+            ),
+            format!("IBAN: {}", self.normalized_account()),
+        ];
+        if let Some(amount) = &self.amount {
+            lines.push(format!("Amount: EUR {amount}"));
+        }
+        match &self.remittance {
+            Some(Remittance::Reference(reference)) => {
+                lines.push(format!("Reference: {}", reference.as_ref()));
+            }
+            Some(Remittance::Text(text)) => lines.push(format!("Text: {}", text.as_ref())),
+            None => {}
+        }
+        lines
+    }
+
+    /// Render the code together with a companion QR-Code for `url`, e.g. a pay-by-link for phones
+    /// without an EPC aware banking app
+    ///
+    /// The companion is a standard QR-Code containing just the URL, not an EPC QR-Code.
+    /// Both are rendered with the same options and returned as `(epc, companion)`.
+    /// The URL must be an absolute `http` or `https` URL with a host, otherwise this fails with
+    /// [`GenerationError::InvalidUrl`].
+    pub fn generate_with_companion(
+        &self,
+        url: &str,
+        options: &RenderOptions,
+    ) -> Result<(image::RgbaImage, image::RgbaImage), GenerationError> {
+        if !url_is_valid(url) {
+            return Err(GenerationError::InvalidUrl(url.to_owned()));
+        }
+        let epc = self.to_rgba_image(options)?;
+
+        let code = QrCode::with_error_correction_level(url, options.ec_level)?;
+        let companion = options.render(&code)?.into_rgba();
+
+        Ok((epc, companion))
+    }
+
+    /// Render the code as ASCII art with `#` for dark and a space for light modules, including the quiet zone
+    ///
+    /// Every module is two characters wide, so that the code keeps its square aspect ratio in monospace fonts.
+    /// The lines are separated by `\n`, without a trailing newline.
+    pub fn to_ascii_art(&self) -> Result<String, GenerationError> {
+        let code = self.qr_code(&RenderOptions::default())?;
+
+        Ok(code
+            .render::<char>()
+            .quiet_zone(true)
+            .module_dimensions(2, 1)
+            .dark_color('#')
+            .light_color(' ')
+            .build())
+    }
+
+    /// Render the code into an existing grayscale buffer, reusing its allocation
+    ///
+    /// The buffer is resized to the dimensions of the code including the quiet zone, as reported by
+    /// [`EpcQr::qr_code_info`], and cleared before drawing. Its previous dimensions don't matter,
+    /// but passing the same buffer for every frame of a live preview avoids allocating once the buffer is large enough.
+    /// For one-shot rendering [`EpcQr::to_rgba_image`] is simpler.
+    pub fn render_into(
+        &self,
+        buffer: &mut image::GrayImage,
+        options: &RenderOptions,
+    ) -> Result<(), GenerationError> {
+        let code = self.qr_code(options)?;
+
+        options.render_into(&code, buffer)
+    }
+
     pub fn generate_image_file(
         &self,
         format: Option<ImageFormat>,
         file_path: &Path,
     ) -> Result<(), GenerationError> {
-        let code = QrCode::new(self.data()?)?;
+        self.generate_image_file_with_options(format, file_path, &RenderOptions::default())
+    }
+
+    /// Generate the image, pass it to `post_process` and write the result to `file_path`
+    ///
+    /// The closure receives the rendered RGBA image including the quiet zone and may modify it in place
+    /// or replace it entirely, e.g. with `*image = image.resize(...)` or after compositing it onto a background.
+    /// Whatever it leaves behind is encoded, so the result is not verified and may no longer be scannable.
+    /// If no format is given it is guessed from the file extension.
+    pub fn generate_image_file_with(
+        &self,
+        format: Option<ImageFormat>,
+        file_path: &Path,
+        post_process: impl FnOnce(&mut image::DynamicImage),
+    ) -> Result<(), GenerationError> {
+        let options = RenderOptions::default();
+        self.check_rendering(format.as_ref(), Some(file_path), &options)?;
+        let code = self.qr_code(&options)?;
 
-        let image = code.render::<Px>().build();
+        let mut image = image::DynamicImage::ImageRgba8(options.render(&code)?.into_rgba());
+        post_process(&mut image);
+        let image = render::Image::from_rgba(image.into_rgba8());
+
+        match format {
+            Some(format) => image.save(format, file_path),
+            None => image.save_guess_format(file_path),
+        }
+    }
+
+    /// Generate the image with the given render options and write it to `file_path`
+    ///
+    /// If no format is given it is guessed from the file extension.
+    pub fn generate_image_file_with_options(
+        &self,
+        format: Option<ImageFormat>,
+        file_path: &Path,
+        options: &RenderOptions,
+    ) -> Result<(), GenerationError> {
+        self.check_rendering(format.as_ref(), Some(file_path), options)?;
+        let code = self.qr_code(options)?;
+
+        let image = options.render(&code)?;
 
         match format {
             Some(format) => image.save(format, file_path)?,
             None => image.save_guess_format(file_path)?,
         }
 
+        if options.verify {
+            options.verify_image_file(&code, &self.data()?, file_path)?;
+        }
+
         Ok(())
     }
+
+    /// Write one PNG image per error correction level into `dir`, e.g. to compare how they scan on a print medium
+    ///
+    /// The files are named after the level and the resulting QR-Code version, e.g. `code-eccL-v3.png`,
+    /// and returned in the order L, M, Q and H. The level of the options is replaced for each image,
+    /// all other options are kept. Levels the payload doesn't fit into with the version or maximal version
+    /// of the options are skipped and missing from the list.
+    pub fn generate_ecc_comparison(
+        &self,
+        dir: &Path,
+        options: &RenderOptions,
+    ) -> Result<Vec<PathBuf>, GenerationError> {
+        let mut paths = Vec::with_capacity(4);
+        for ec_level in [
+            qrcode::EcLevel::L,
+            qrcode::EcLevel::M,
+            qrcode::EcLevel::Q,
+            qrcode::EcLevel::H,
+        ] {
+            let options = options
+                .clone()
+                .with_ec_level(ec_level)
+                .with_auto_downgrade_ecc(false);
+            let version = match self
+                .qr_code(&options)
+                .and_then(|code| options.check_version(&code).map(|()| code.version()))
+            {
+                Ok(qrcode::Version::Normal(version) | qrcode::Version::Micro(version)) => version,
+                Err(
+                    GenerationError::PayloadTooLargeForVersion { .. }
+                    | GenerationError::VersionTooHigh { .. },
+                ) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::info!(
+                        ?ec_level,
+                        "payload doesn't fit, skipping error correction level"
+                    );
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+
+            let path = dir.join(format!("code-ecc{ec_level:?}-v{version}.png"));
+            self.generate_image_file_with_options(Some(ImageFormat::png()), &path, &options)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
 }
 
-impl ToString for EpcQr {
-    fn to_string(&self) -> String {
-        let mut data = String::with_capacity(Self::MAX_LENGTH_BYTES);
+/// Two codes are equal if they encode the same payload, even if their input was formatted differently
+///
+/// The payload contains the IBAN without whitespace and in uppercase and the purpose trimmed and in uppercase,
+/// as well as the name and info after whitespace normalization, if enabled with [`EpcQr::with_normalize_whitespace`].
+/// All settings changing the payload are compared, e.g. the EPC version after inferring it and the line ending.
+/// The internal reference and the [`Validator`] are ignored, as they don't change the payload.
+/// Equal codes have the same [`EpcQr::payload_fingerprint`].
+impl<S: AsRef<str>, T: AsRef<str>> PartialEq<EpcQr<T>> for EpcQr<S> {
+    fn eq(&self, other: &EpcQr<T>) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
 
-        let version = if self.bic.is_some() {
-            "001\n"
-        } else {
-            "002\n"
-        };
+impl<S: AsRef<str>> Eq for EpcQr<S> {}
 
+/// Consistent with the [`PartialEq`] implementation, so equal codes have equal hashes
+impl<S: AsRef<str>> std::hash::Hash for EpcQr<S> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.to_string().hash(state);
+    }
+}
 
-        data.push_str("BCD\n");
-        data.push_str(version);
+impl<S: AsRef<str>> Display for EpcQr<S> {
+    /// The payload as encoded into the QR-Code, one field per line as listed by [`EpcQr::as_fields`]
+    ///
+    /// Trailing unset fields are omitted, unless [`EpcQr::with_emit_trailing_empty`] is enabled.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fields = self.as_fields();
+        let used = self.line_count(&fields);
 
-        data.push_str("1\n");
-        data.push_str("SCT\n");
-        if let Some(bic) = &self.bic {
-            data.push_str(bic)
+        for (idx, (_, value)) in fields[..used].iter().enumerate() {
+            if idx != 0 {
+                f.write_str(self.line_ending.as_str())?;
+            }
+            f.write_str(value.as_deref().unwrap_or_default())?;
         }
-        data.push('\n');
-        data.push_str(&self.beneficiary_name);
-        data.push('\n');
-        data.push_str(&self.beneficiary_account);
+        Ok(())
+    }
+}
 
-        if let Some(amount) = &self.amount {
-            data.push('\n');
-            let amount = if amount.cent % 10 == 0 {
-                format!("{}.{}", amount.euro, amount.cent / 10)
-            } else {
-                format!("{}.{:02}", amount.euro, amount.cent)
-            };
-            data.push_str(&format!("EUR{amount}"));
-        } else if self.purpose.is_some() || self.remittance.is_some() || self.info.is_some() {
-            data.push('\n');
-        }
-
-        if let Some(purpose) = &self.purpose {
-            data.push('\n');
-            data.push_str(purpose);
-        } else if self.remittance.is_some() || self.info.is_some() {
-            data.push('\n');
-        }
-
-        if let Some(remittance) = &self.remittance {
-            match remittance {
-                Remittance::Reference(reference) => {
-                    data.push_str(reference);
-                    if self.info.is_some(){
-                        data.push('\n');
-                     }
-                },
-                Remittance::Text(text) => {
-                    data.push('\n');
-                    data.push_str(text);
-                },
-            }
+impl FromStr for EpcQr {
+    type Err = InvalidPayload;
 
-        } else if self.info.is_some(){
-            data.push('\n');
-            data.push('\n')
+    /// Parse a payload as produced by [`EpcQr::to_string`], e.g. after scanning an existing code
+    ///
+    /// Both `\n` and `\r\n` line endings are accepted and trailing empty lines may be omitted.
+    /// Empty lines up to the info or the remittance text are kept, so that `to_string` reproduces the payload,
+    /// see [`EpcQr::with_emit_trailing_empty`] and [`EpcQr::with_empty_remittance`].
+    /// Lines after the info become extra fields, see [`EpcQr::with_extra_fields`].
+    /// The fields are only split up, not validated, use [`EpcQr::validate_verbose`] for that.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line_ending = if s.contains("\r\n") {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
         };
+        let lines = s.split(line_ending.as_str()).collect::<Vec<_>>();
+        let line = |idx: usize| lines.get(idx).copied().unwrap_or_default();
+        let optional = |idx: usize| Some(line(idx)).filter(|line| !line.is_empty());
 
-         if let Some(info) = &self.info {
-            data.push('\n');
-            data.push_str(info);
+        if line(0) != spec::SERVICE_TAG {
+            return Err(InvalidPayload::InvalidServiceTag(line(0).to_owned()));
+        }
+        let version = match line(1) {
+            spec::VERSION_1 => EpcVersion::V1,
+            spec::VERSION_2 => EpcVersion::V2,
+            version => return Err(InvalidPayload::InvalidVersion(version.to_owned())),
+        };
+        let character_set = CharacterSet::VARIANTS
+            .into_iter()
+            .find(|character_set| (character_set.clone() as u8).to_string() == line(2))
+            .ok_or_else(|| InvalidPayload::InvalidCharacterSet(line(2).to_owned()))?;
+        if line(3) != spec::SCT_IDENTIFICATION {
+            return Err(InvalidPayload::InvalidIdentification(line(3).to_owned()));
         }
+        if lines.len() < 7 {
+            return Err(InvalidPayload::MissingAccount);
+        }
+
+        let amount = optional(7)
+            .map(|amount| {
+                let amount = amount
+                    .strip_prefix("EUR")
+                    .ok_or_else(|| InvalidPayload::InvalidCurrency(amount.to_owned()))?;
+                Ok::<_, InvalidPayload>(Amount::from_payload_str(amount)?)
+            })
+            .transpose()?;
+        let remittance = match (optional(9), optional(10)) {
+            (Some(reference), None) => Some(Remittance::Reference(reference.to_owned())),
+            (None, Some(text)) => Some(Remittance::Text(text.to_owned())),
+            (None, None) => None,
+            (Some(_), Some(_)) => return Err(InvalidEpcCode::DuplicateRemittance.into()),
+        };
 
-        data
+        let code = EpcQr::new(line(5).to_owned(), line(6).to_owned())
+            .with_epc_version(Some(version))
+            .with_character_set(character_set)
+            .with_line_ending(line_ending)
+            .with_bic(optional(4).map(str::to_owned))
+            .with_amount(amount)
+            .with_purpose(optional(8).map(str::to_owned))
+            .with_remittance(remittance)
+            .with_info(optional(11).map(str::to_owned))
+            .with_extra_fields(
+                lines
+                    .get(spec::MAX_LINES..)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|&field| field.to_owned())
+                    .collect(),
+            );
+
+        let used = code.line_count(&code.as_fields());
+        Ok(match lines.len() {
+            len if len <= used => code,
+            spec::MAX_LINES => code.with_emit_trailing_empty(true),
+            // the payload ends with the empty reference and text lines
+            len if len == spec::MAX_LINES - 1 && code.remittance.is_none() => {
+                code.with_empty_remittance()
+            }
+            _ => code,
+        })
     }
 }
 
+/// Errors when parsing a payload with [`EpcQr::from_str`]
+#[derive(Debug, thiserror::Error)]
+pub enum InvalidPayload {
+    #[error("Expected the service tag 'BCD', but found '{0}'")]
+    InvalidServiceTag(String),
+    #[error("Expected the version '001' or '002', but found '{0}'")]
+    InvalidVersion(String),
+    #[error("Expected a character set between 1 and 8, but found '{0}'")]
+    InvalidCharacterSet(String),
+    #[error("Expected the identification 'SCT', but found '{0}'")]
+    InvalidIdentification(String),
+    #[error("The payload ends before the account of the beneficiary")]
+    MissingAccount,
+    #[error("Expected the amount to start with 'EUR', but found '{0}'")]
+    InvalidCurrency(String),
+    #[error("{0}")]
+    InvalidAmount(#[from] InvalidAmount),
+    #[error("{0}")]
+    InvalidEpcCode(#[from] InvalidEpcCode),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum InvalidEpcCode {
-    #[error("Total data is larger than the maximal allowed 331 bytes!")]
+    #[error(
+        "Total data is larger than the maximal allowed {} bytes!",
+        spec::MAX_PAYLOAD_BYTES
+    )]
     TooLargeTotal,
+    #[error("The fields are valid on their own, but together need {total} bytes, more than the allowed {max} bytes")]
+    CombinedTooLarge { total: usize, max: usize },
     #[error("At most one remittance field (text/reference) may be specified!")]
     DuplicateRemittance,
+    #[error("The character '{character}' can't be encoded in {character_set}")]
+    UnencodableCharacter {
+        character: char,
+        character_set: CharacterSet,
+    },
+    #[error("The amount must not be zero, leave the amount unset for a code without an amount")]
+    ZeroAmount,
     #[error("At least one field had an invalid length")]
     InvalidFieldLength {
         invalid_bic: bool,
@@ -391,60 +1724,611 @@ pub enum InvalidEpcCode {
     },
 }
 
-#[derive(Debug, Clone)]
+impl InvalidEpcCode {
+    /// The names of the invalid fields, e.g. `["beneficiary_name", "amount"]`
+    ///
+    /// Only [`InvalidEpcCode::InvalidFieldLength`] and [`InvalidEpcCode::ZeroAmount`] mark fields as invalid,
+    /// for all other variants this is empty.
+    pub fn field_names(&self) -> Vec<&'static str> {
+        match self {
+            InvalidEpcCode::InvalidFieldLength {
+                invalid_bic,
+                invalid_name,
+                invalid_iban,
+                invalid_amount,
+                invalid_purpose,
+                invalid_remittance,
+                invalid_info,
+            } => [
+                (invalid_bic, "bic"),
+                (invalid_name, "beneficiary_name"),
+                (invalid_iban, "beneficiary_account"),
+                (invalid_amount, "amount"),
+                (invalid_purpose, "purpose"),
+                (invalid_remittance, "remittance"),
+                (invalid_info, "info"),
+            ]
+            .into_iter()
+            .filter_map(|(invalid, name)| invalid.then_some(name))
+            .collect(),
+            InvalidEpcCode::ZeroAmount => vec!["amount"],
+            InvalidEpcCode::TooLargeTotal
+            | InvalidEpcCode::CombinedTooLarge { .. }
+            | InvalidEpcCode::DuplicateRemittance
+            | InvalidEpcCode::UnencodableCharacter { .. } => Vec::new(),
+        }
+    }
+
+    /// Bundle a list of field errors into the [`InvalidEpcCode::InvalidFieldLength`] variant
+    ///
+    /// Returns `None` if the list is empty. Invalid extra fields don't mark any of the standard fields,
+    /// they are only listed by [`EpcQr::validate_verbose`].
+    pub fn from_field_errors(errors: &[FieldError]) -> Option<Self> {
+        match errors {
+            [] => return None,
+            [FieldError::ZeroAmount] => return Some(Self::ZeroAmount),
+            _ => {}
+        }
+        let has = |predicate: fn(&FieldError) -> bool| errors.iter().any(predicate);
+        Some(Self::InvalidFieldLength {
+            invalid_bic: has(|err| {
+                matches!(
+                    err,
+                    FieldError::InvalidBicLength(_) | FieldError::InvalidBic | FieldError::MissingBic
+                )
+            }),
+            invalid_name: has(|err| matches!(err, FieldError::InvalidNameLength(_))),
+            invalid_iban: has(|err| {
+                matches!(
+                    err,
+                    FieldError::InvalidIbanLength(_)
+                        | FieldError::InvalidIban
+                        | FieldError::NonSepaIban { .. }
+                )
+            }),
+            invalid_amount: has(|err| {
+                matches!(
+                    err,
+                    FieldError::InvalidAmount { .. }
+                        | FieldError::ZeroAmount
+                        | FieldError::SingleDecimalAmount { .. }
+                )
+            }),
+            invalid_purpose: has(|err| matches!(err, FieldError::InvalidPurposeLength(_))),
+            invalid_remittance: has(|err| {
+                matches!(
+                    err,
+                    FieldError::InvalidReferenceLength(_) | FieldError::InvalidTextLength(_)
+                )
+            }),
+            invalid_info: has(|err| matches!(err, FieldError::InvalidInfoLength(_))),
+        })
+    }
+}
+
+/// Marks the start of the info holding the overflow of a split remittance text
+const REMITTANCE_CONTINUATION: &str = "...";
+
+/// Country codes of IBANs from countries and territories participating in the SEPA schemes
+const SEPA_COUNTRIES: [&str; 37] = [
+    "AD", "AT", "BE", "BG", "CH", "CY", "CZ", "DE", "DK", "EE", "ES", "FI", "FR", "GB", "GI", "GR",
+    "HR", "HU", "IE", "IS", "IT", "LI", "LT", "LU", "LV", "MC", "MT", "NL", "NO", "PL", "PT", "RO",
+    "SE", "SI", "SK", "SM", "VA",
+];
+
+/// IBAN lengths and the country codes of the countries listed in the SWIFT IBAN registry with that length
+const IBAN_LENGTHS: [(usize, &[&str]); 18] = [
+    (15, &["NO"]),
+    (16, &["BE"]),
+    (18, &["DK", "FI", "FK", "FO", "GL", "NL", "SD"]),
+    (19, &["MK", "SI"]),
+    (20, &["AT", "BA", "EE", "KZ", "LT", "LU", "MN", "XK"]),
+    (21, &["CH", "HR", "LI", "LV"]),
+    (
+        22,
+        &["BG", "BH", "CR", "DE", "GB", "GE", "IE", "ME", "RS", "VA"],
+    ),
+    (23, &["AE", "GI", "IL", "IQ", "OM", "SO", "TL"]),
+    (
+        24,
+        &[
+            "AD", "CZ", "ES", "MD", "PK", "RO", "SA", "SE", "SK", "TN", "VG",
+        ],
+    ),
+    (25, &["LY", "PT", "ST"]),
+    (26, &["IS", "TR"]),
+    (27, &["BI", "DJ", "FR", "GR", "IT", "MC", "MR", "SM"]),
+    (
+        28,
+        &[
+            "AL", "AZ", "BY", "CY", "DO", "GT", "HU", "LB", "NI", "PL", "SV",
+        ],
+    ),
+    (29, &["BR", "EG", "PS", "QA", "UA"]),
+    (30, &["JO", "KW", "MU", "YE"]),
+    (31, &["MT", "SC"]),
+    (32, &["LC"]),
+    (33, &["RU"]),
+];
+
+/// Check whether `iban` is a well-formed IBAN with matching check digits
+///
+/// Only the electronic format without spaces is accepted, as required for the payload, letters must be uppercase.
+/// Use [`find_iban`] for IBANs in the print format split into groups by spaces.
+/// This doesn't check the country specific length or format of the account number.
+pub fn iban_is_valid(iban: &str) -> bool {
+    let chars = || iban.chars();
+    let length = chars().count();
+    if !(15..=34).contains(&length) {
+        return false;
+    }
+
+    let mut prefix = chars();
+    let country_ok = prefix.by_ref().take(2).all(|c| c.is_ascii_uppercase());
+    let check_digits_ok = prefix.take(2).all(|c| c.is_ascii_digit());
+    let bban_ok = chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+    if !country_ok || !check_digits_ok || !bban_ok {
+        return false;
+    }
+
+    // move the country code and check digits to the end and interpret letters as 10 to 35
+    let remainder = chars()
+        .skip(4)
+        .chain(chars().take(4))
+        .fold(0u32, |remainder, c| {
+            let value = c.to_digit(36).expect("only digits and ascii letters remain");
+            let shift = if value < 10 { 10 } else { 100 };
+            (remainder * shift + value) % 97
+        });
+
+    remainder == 1
+}
+
+/// Find the first valid IBAN in `text`, e.g. text copied from a banking app
+///
+/// The IBAN may be in the print format split into groups by spaces, the result is in the electronic
+/// format without spaces. Punctuation around the IBAN is ignored.
+/// Groups are only joined up to the IBAN length of the country, so that following numbers aren't taken
+/// for a part of the IBAN, for countries missing from the IBAN registry the shortest valid IBAN is used.
+pub fn find_iban(text: &str) -> Option<String> {
+    let words = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_ascii_alphanumeric()))
+        .collect::<Vec<_>>();
+
+    (0..words.len()).find_map(|start| {
+        let mut candidate = String::new();
+        for word in &words[start..] {
+            candidate.push_str(word);
+            let length = candidate.get(..2).and_then(iban_length);
+            match length {
+                Some(length) if candidate.len() < length => continue,
+                Some(length) if length < candidate.len() => return None,
+                _ if spec::MAX_IBAN_LENGTH < candidate.len() => return None,
+                _ => {}
+            }
+            if iban_is_valid(&candidate) {
+                return Some(candidate);
+            }
+            if length.is_some() {
+                return None;
+            }
+        }
+        None
+    })
+}
+
+/// The length of the IBANs of `country` according to the IBAN registry, if it is listed
+fn iban_length(country: &str) -> Option<usize> {
+    IBAN_LENGTHS
+        .iter()
+        .find(|(_, countries)| countries.contains(&country))
+        .map(|(length, _)| *length)
+}
+
+/// Check whether `url` is an absolute `http` or `https` URL with a host and without whitespace
+fn url_is_valid(url: &str) -> bool {
+    let Some(rest) = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    !host.is_empty()
+        && !host.starts_with(':')
+        && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// Check whether `bic` is a well-formed BIC
+///
+/// A BIC consists of a four letter bank code, a two letter country code, a two character location code
+/// and optionally a three character branch code.
+pub fn bic_is_valid(bic: &str) -> bool {
+    if !spec::BIC_LENGTHS.contains(&bic.len()) {
+        return false;
+    }
+    bic.chars().enumerate().all(|(idx, c)| match idx {
+        0..=5 => c.is_ascii_uppercase(),
+        _ => c.is_ascii_uppercase() || c.is_ascii_digit(),
+    })
+}
+
+/// A suspicious but valid field as reported by [`EpcQr::warnings`]
+#[cfg(feature = "security")]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum Warning {
+    #[error("The beneficiary name mixes scripts, e.g. Latin and Cyrillic look-alike characters")]
+    ConfusableName,
+}
+
+/// The results of all checks of a code at once, see [`EpcQr::validation_report`]
+///
+/// With the `serde` feature the report can be serialized, e.g. to return it as JSON from a web API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ValidationReport {
+    /// Whether the code can be generated, i.e. there are neither field errors nor a payload error
+    pub valid: bool,
+    /// The problems of the individual fields, as reported by [`EpcQr::validate_verbose`]
+    pub field_errors: Vec<FieldError>,
+    /// The problem of the payload as a whole, e.g. exceeding the size limit or a character
+    /// that can't be encoded in the character set
+    pub payload_error: Option<String>,
+    /// Fields that are valid but suspicious, as reported by [`EpcQr::warnings`]
+    #[cfg(feature = "security")]
+    pub warnings: Vec<Warning>,
+}
+
+/// A single invalid field as reported by [`EpcQr::validate_verbose`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FieldError {
+    #[error("The BIC is mandatory for Version 1")]
+    MissingBic,
+    #[error(
+        "The BIC must be {} or {} characters long, but was {0}",
+        spec::BIC_LENGTHS[0],
+        spec::BIC_LENGTHS[1]
+    )]
+    InvalidBicLength(usize),
+    #[error("The BIC is not formatted correctly")]
+    InvalidBic,
+    #[error(
+        "The beneficiary name must be 1 to {} characters long, but was {0}",
+        spec::MAX_NAME_LENGTH
+    )]
+    InvalidNameLength(usize),
+    #[error(
+        "The IBAN must be 1 to {} characters long, but was {0}",
+        spec::MAX_IBAN_LENGTH
+    )]
+    InvalidIbanLength(usize),
+    #[error("The IBAN is not formatted correctly or its check digits don't match")]
+    InvalidIban,
+    #[error("The IBAN is from '{country}', which is not part of the SEPA scheme")]
+    NonSepaIban { country: String },
+    #[error(
+        "The amount must be between {} and {}, but was {euro}.{cent:02}",
+        Amount::MIN,
+        Amount::MAX
+    )]
+    InvalidAmount { euro: u32, cent: u8 },
+    #[error("The amount must not be zero, leave the amount unset for a code without an amount")]
+    ZeroAmount,
+    #[error("The amount {euro}.{cent:02} is encoded with a single decimal, which some banks reject")]
+    SingleDecimalAmount { euro: u32, cent: u8 },
+    #[error(
+        "The purpose must be 1 to {} characters long, but was {0}",
+        spec::MAX_PURPOSE_LENGTH
+    )]
+    InvalidPurposeLength(usize),
+    #[error(
+        "The remittance reference must be 1 to {} characters long, but was {0}",
+        spec::MAX_REFERENCE_LENGTH
+    )]
+    InvalidReferenceLength(usize),
+    #[error(
+        "The remittance text must be 1 to {} characters long, but was {0}",
+        spec::MAX_TEXT_LENGTH
+    )]
+    InvalidTextLength(usize),
+    #[error(
+        "The beneficiary to originator information must be 1 to {} characters long, but was {0}",
+        spec::MAX_INFO_LENGTH
+    )]
+    InvalidInfoLength(usize),
+    #[error("The extra field {0} must not contain a line break")]
+    InvalidExtraField(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Amount {
-    // 0 <= euro <= 999999999
+    // Amount::MIN <= euro.cent <= Amount::MAX
     euro: u32,
     // 0 <= cent < 100
-    // unless euro is 0 then  0 < cent
     cent: u8,
 }
 
+impl Amount {
+    /// The smallest allowed amount, 0.01 euro
+    pub const MIN: Amount = Amount { euro: 0, cent: 1 };
+    /// The largest allowed amount, 999999999.99 euro
+    pub const MAX: Amount = Amount {
+        euro: 999_999_999,
+        cent: 99,
+    };
+
+    /// Create an amount from whole euros and the cents on top, e.g. `12` and `50` for 12.50 euro
+    pub fn new(euros: u32, cents: u8) -> Result<Self, InvalidAmount> {
+        if euros == 0 && cents == 0 {
+            return Err(InvalidAmount::Zero);
+        }
+        if !Self::is_in_range(euros, cents) {
+            return Err(InvalidAmount::OutOfRange {
+                euro: euros,
+                cent: cents,
+            });
+        }
+        Ok(Self {
+            euro: euros,
+            cent: cents,
+        })
+    }
+
+    /// Create an amount from integer cents, e.g. `1250` for 12.50 euro
+    pub fn from_cents(cents: u64) -> Result<Self, InvalidAmount> {
+        if cents == 0 {
+            return Err(InvalidAmount::Zero);
+        }
+        // saturate, anything that doesn't fit is out of range anyway
+        let euro = u32::try_from(cents / 100).unwrap_or(u32::MAX);
+        let cent = (cents % 100) as u8;
+        if !Self::is_in_range(euro, cent) {
+            return Err(InvalidAmount::OutOfRange { euro, cent });
+        }
+        Ok(Self { euro, cent })
+    }
+
+    /// The whole euros, e.g. `12` for 12.50 euro
+    pub fn euros(&self) -> u32 {
+        self.euro
+    }
+
+    /// The cents on top of the whole euros, always below 100, e.g. `50` for 12.50 euro
+    pub fn cents(&self) -> u8 {
+        self.cent
+    }
+
+    /// The whole amount in cents, e.g. `1250` for 12.50 euro, the inverse of [`Amount::from_cents`]
+    pub fn total_cents(&self) -> u64 {
+        u64::from(self.euro) * 100 + u64::from(self.cent)
+    }
+
+    /// Parse an amount that may leave out the decimal places of whole euros, e.g. `12` for 12.00 euro
+    ///
+    /// Payloads and the other formats read by this crate omit them, otherwise this is the same as [`str::parse`].
+    pub fn from_payload_str(s: &str) -> Result<Self, InvalidAmount> {
+        if s.contains(['.', ',']) {
+            return s.parse();
+        }
+        let euro = parse_digits(s).map_err(|source| InvalidAmount::InvalidEuro {
+            input: s.to_string(),
+            euro: s.to_string(),
+            source,
+        })?;
+        Self::new(euro, 0)
+    }
+
+    /// Whether `euro` and `cent` form an amount between [`Amount::MIN`] and [`Amount::MAX`]
+    fn is_in_range(euro: u32, cent: u8) -> bool {
+        cent < 100
+            && (Self::MIN.euro, Self::MIN.cent) <= (euro, cent)
+            && (euro, cent) <= (Self::MAX.euro, Self::MAX.cent)
+    }
+}
+
+impl Display for Amount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{:02}", self.euro, self.cent)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum InvalidAmount {
-    #[error("The amount must be between 0.01 and 999999999.99, but was {euro}.{cent:02}")]
+    #[error("The amount must not be zero, leave the amount unset for a code without an amount")]
+    Zero,
+    #[error(
+        "The amount must be between {} and {}, but was {euro}.{cent:02}",
+        Amount::MIN,
+        Amount::MAX
+    )]
     OutOfRange {
         euro: u32,
         cent: u8,
     },
-    #[error("Failed to parse Amount: {0}")]
-    ParseIntError(#[from] std::num::ParseIntError),
-    #[error("Invalid format, expected #.##, but couldn't find '.'")]
+    #[error("Couldn't parse euros '{euro}' in '{input}': {source}")]
+    InvalidEuro {
+        input: String,
+        euro: String,
+        source: std::num::ParseIntError,
+    },
+    #[error("Couldn't parse cents '{cent}' in '{input}': {source}")]
+    InvalidCent {
+        input: String,
+        cent: String,
+        source: std::num::ParseIntError,
+    },
+    #[error("At most two decimal places are allowed, but '{input}' has more")]
+    TooManyDecimals { input: String },
+    #[error("Invalid format, expected #.## or #,##, but couldn't find '.' or ','")]
     NoSeparator
 }
 
 impl FromStr for Amount {
     type Err = InvalidAmount;
 
+    /// Parse an amount in euro with one or two decimal places, separated by either '.' or ','
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (euro, cent) = s.split_once('.').ok_or(InvalidAmount::NoSeparator)?;
-        let euro  = euro.parse()?;
-        let cent = cent.parse()?;
-        if 999999999 < euro || 99 < cent || (euro == 0 && cent == 0) {
+        let (euro, cent) = s
+            .split_once(['.', ','])
+            .ok_or(InvalidAmount::NoSeparator)?;
+        if 2 < cent.len() {
+            return Err(InvalidAmount::TooManyDecimals {
+                input: s.to_string(),
+            });
+        }
+        let euro = parse_digits(euro).map_err(|source| InvalidAmount::InvalidEuro {
+            input: s.to_string(),
+            euro: euro.to_string(),
+            source,
+        })?;
+        let cent = parse_digits::<u8>(cent)
+            .map(|value| if cent.len() == 1 { value * 10 } else { value })
+            .map_err(|source| InvalidAmount::InvalidCent {
+                input: s.to_string(),
+                cent: cent.to_string(),
+                source,
+            })?;
+        if euro == 0 && cent == 0 {
+            return Err(InvalidAmount::Zero);
+        }
+        if !Self::is_in_range(euro, cent) {
             return Err(InvalidAmount::OutOfRange { euro, cent });
         }
         Ok(Self {euro, cent})
     }
 }
 
+/// Parse a number consisting only of ASCII digits
+///
+/// Unlike [`str::parse`] this rejects a leading `+`, so that e.g. `12.+5` isn't read as 12.05.
+fn parse_digits<T: FromStr<Err = std::num::ParseIntError>>(
+    digits: &str,
+) -> Result<T, std::num::ParseIntError> {
+    if digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        digits.parse()
+    } else {
+        // the sign on its own fails with the same error as any other invalid digit
+        "+".parse()
+    }
+}
+
 #[derive(Debug, Clone)]
-pub enum Remittance {
+pub enum Remittance<S = String> {
     /// AT-05 Remittance information (Structured/Reference)
     /// (max. 35 characters)
-    Reference(String),
+    Reference(S),
     /// AT-05 Remittance information (Unstructured/Text)
     /// (max. 140 characters)
-    Text(String),
+    Text(S),
 }
 
 impl Remittance {
+    /// The maximal length of a [`Remittance::Reference`] in characters
+    pub const MAX_REFERENCE_LEN: usize = spec::MAX_REFERENCE_LENGTH;
+    /// The maximal length of a [`Remittance::Text`] in characters
+    pub const MAX_TEXT_LEN: usize = spec::MAX_TEXT_LENGTH;
+}
+
+impl<S: AsRef<str>> Remittance<S> {
     pub fn text(&self) -> &str {
         let (Remittance::Reference(text) | Remittance::Text(text)) = self;
-        text
+        text.as_ref()
+    }
+
+    /// Check the length of the remittance on its own, e.g. to validate the field before attaching it to an [`EpcQr`]
+    ///
+    /// References must be 1 to [`Remittance::MAX_REFERENCE_LEN`] and texts 1 to [`Remittance::MAX_TEXT_LEN`]
+    /// characters long. This is the same check [`EpcQr::validate`] does for the remittance.
+    pub fn validate(&self) -> Result<(), InvalidEpcCode> {
+        match self.length_error() {
+            Some(err) => Err(InvalidEpcCode::from_field_errors(&[err])
+                .expect("a single error is bundled into an error")),
+            None => Ok(()),
+        }
+    }
+
+    /// The error for a reference or text of invalid length, if any
+    pub(crate) fn length_error(&self) -> Option<FieldError> {
+        let length = self.text().chars().count();
+        match self {
+            Remittance::Reference(_) if !(1..=Remittance::MAX_REFERENCE_LEN).contains(&length) => {
+                Some(FieldError::InvalidReferenceLength(length))
+            }
+            Remittance::Text(_) if !(1..=Remittance::MAX_TEXT_LEN).contains(&length) => {
+                Some(FieldError::InvalidTextLength(length))
+            }
+            Remittance::Reference(_) | Remittance::Text(_) => None,
+        }
+    }
+
+    /// Convert into a remittance owning its text
+    pub fn into_owned(self) -> Remittance {
+        match self {
+            Remittance::Reference(reference) => {
+                Remittance::Reference(reference.as_ref().to_string())
+            }
+            Remittance::Text(text) => Remittance::Text(text.as_ref().to_string()),
+        }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Version of the EPC QR-Code format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EpcVersion {
+    /// Version 001, the BIC is mandatory
+    V1,
+    /// Version 002, the BIC is optional inside the EEA
+    V2,
+}
+
+/// A field of the payload, see [`EpcQr::field_line_index`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EpcField {
+    /// The service tag `BCD`
+    ServiceTag,
+    Version,
+    CharacterSet,
+    /// The identification `SCT`
+    Identification,
+    Bic,
+    BeneficiaryName,
+    BeneficiaryAccount,
+    Amount,
+    Purpose,
+    /// The structured remittance reference
+    Reference,
+    /// The unstructured remittance text
+    Text,
+    Info,
+    /// The extra field at the given index, see [`EpcQr::with_extra_fields`]
+    Extra(usize),
+}
+
+/// Separator between the fields of the payload
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`, as used by the specification
+    #[default]
+    Lf,
+    /// `\r\n`, which some readers tolerate or expect
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CharacterSet {
     Utf8 = 1,
     ISO8859_01 = 2,
@@ -455,3 +2339,85 @@ pub enum CharacterSet {
     ISO8859_10 = 7,
     ISO8859_15 = 8,
 }
+
+impl CharacterSet {
+    const VARIANTS: [Self; 8] = [
+        Self::Utf8,
+        Self::ISO8859_01,
+        Self::ISO8859_02,
+        Self::ISO8859_04,
+        Self::ISO8859_05,
+        Self::ISO8859_07,
+        Self::ISO8859_10,
+        Self::ISO8859_15,
+    ];
+
+    /// The canonical name, e.g. `iso-8859-1`
+    pub fn name(&self) -> &'static str {
+        match self {
+            CharacterSet::Utf8 => "utf-8",
+            CharacterSet::ISO8859_01 => "iso-8859-1",
+            CharacterSet::ISO8859_02 => "iso-8859-2",
+            CharacterSet::ISO8859_04 => "iso-8859-4",
+            CharacterSet::ISO8859_05 => "iso-8859-5",
+            CharacterSet::ISO8859_07 => "iso-8859-7",
+            CharacterSet::ISO8859_10 => "iso-8859-10",
+            CharacterSet::ISO8859_15 => "iso-8859-15",
+        }
+    }
+
+    /// Alternative names accepted when parsing
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            CharacterSet::Utf8 => &["utf8"],
+            CharacterSet::ISO8859_01 => &["iso8859-1", "latin1"],
+            CharacterSet::ISO8859_02 => &["iso8859-2", "latin2"],
+            CharacterSet::ISO8859_04 => &["iso8859-4", "latin4"],
+            CharacterSet::ISO8859_05 => &["iso8859-5", "cyrillic"],
+            CharacterSet::ISO8859_07 => &["iso8859-7", "greek"],
+            CharacterSet::ISO8859_10 => &["iso8859-10", "latin6"],
+            CharacterSet::ISO8859_15 => &["iso8859-15", "latin9"],
+        }
+    }
+}
+
+impl Display for CharacterSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for CharacterSet {
+    type Err = UnknownCharacterSet;
+
+    /// Parse the name of a character set ignoring case, e.g. `utf-8`, `iso-8859-15` or `latin1`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        Self::VARIANTS
+            .into_iter()
+            .find(|character_set| {
+                std::iter::once(character_set.name())
+                    .chain(character_set.aliases().iter().copied())
+                    .any(|candidate| candidate.eq_ignore_ascii_case(name))
+            })
+            .ok_or_else(|| UnknownCharacterSet(s.to_owned()))
+    }
+}
+
+#[cfg(feature = "cli")]
+impl ValueEnum for CharacterSet {
+    fn value_variants<'a>() -> &'a [Self] {
+        &Self::VARIANTS
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.name()).aliases(self.aliases()))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Unknown character set '{0}', expected one of {names}",
+    names = CharacterSet::VARIANTS.map(|character_set| character_set.name()).join(", ")
+)]
+pub struct UnknownCharacterSet(String);
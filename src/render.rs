@@ -0,0 +1,1456 @@
+//! Options for rendering EPC QR-Codes into images
+
+use std::io::Cursor;
+use std::path::Path;
+
+#[cfg(feature = "qoi")]
+use arqoii::types::QoiHeader;
+use image::codecs::png::{CompressionType, FilterType as PngFilterType, PngEncoder};
+use image::error::{ImageError, LimitError, LimitErrorKind};
+use image::imageops::FilterType;
+use image::{DynamicImage, GrayImage, ImageBuffer, ImageEncoder, Luma, Rgba, RgbaImage};
+use jpeg_encoder::{ColorType, Encoder as JpegEncoder, SamplingFactor};
+use qrcode::render::Pixel;
+use qrcode::{EcLevel, QrCode, Version};
+
+use crate::{font, EpcQr, GenerationError, ImageFormat};
+
+/// How to render an EPC QR-Code into an image, independent of the payment data
+///
+/// The default renders the smallest QR-Code version fitting the payload at error correction level M,
+/// with 8 pixel modules and a quiet zone of 4 modules on each side.
+///
+/// The options are `Send` and `Sync`, one instance can be shared by threads rendering in parallel.
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub(crate) ec_level: EcLevel,
+    pub(crate) auto_downgrade_ecc: bool,
+    pub(crate) version: Option<Version>,
+    pub(crate) max_version: Option<u8>,
+    pub(crate) module_size: u32,
+    pub(crate) quiet_zone: QuietZone,
+    pub(crate) verify: bool,
+    pub(crate) debug_grid: bool,
+    pub(crate) reproducible: bool,
+    pub(crate) jpeg_quality: u8,
+    pub(crate) chroma_subsampling: ChromaSubsampling,
+    pub(crate) dark: [u8; 3],
+    pub(crate) light: [u8; 3],
+    pub(crate) module_shape: ModuleShape,
+    pub(crate) finder: Option<[u8; 3]>,
+    pub(crate) quiet_zone_color: Option<[u8; 3]>,
+    pub(crate) frame: Option<(u32, [u8; 3])>,
+    pub(crate) svg_caption: bool,
+    pub(crate) target_width: Option<u32>,
+    pub(crate) resize_filter: FilterType,
+    pub(crate) canvas_size: Option<u32>,
+    pub(crate) rotation: Rotation,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            ec_level: EcLevel::M,
+            auto_downgrade_ecc: false,
+            version: None,
+            max_version: None,
+            module_size: 8,
+            quiet_zone: QuietZone::default(),
+            verify: false,
+            debug_grid: false,
+            reproducible: false,
+            jpeg_quality: DEFAULT_JPEG_QUALITY,
+            chroma_subsampling: ChromaSubsampling::None,
+            dark: [0, 0, 0],
+            light: [255, 255, 255],
+            module_shape: ModuleShape::Square,
+            finder: None,
+            quiet_zone_color: None,
+            frame: None,
+            svg_caption: false,
+            target_width: None,
+            resize_filter: FilterType::Nearest,
+            canvas_size: None,
+            rotation: Rotation::None,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Set the error correction level, the default is M
+    ///
+    /// Higher levels tolerate more damage to the printed code, but need a larger QR-Code version.
+    #[must_use]
+    pub fn with_ec_level(mut self, ec_level: EcLevel) -> Self {
+        self.ec_level = ec_level;
+        self
+    }
+
+    /// Retry at progressively lower error correction levels, down to [`EcLevel::L`],
+    /// if the payload doesn't fit the fixed or maximal version at the selected level
+    ///
+    /// Disabled by default. Check the level actually used with [`EpcQr::qr_code_info`](crate::EpcQr::qr_code_info).
+    #[must_use]
+    pub fn with_auto_downgrade_ecc(mut self, auto_downgrade_ecc: bool) -> Self {
+        self.auto_downgrade_ecc = auto_downgrade_ecc;
+        self
+    }
+
+    /// The error correction levels to try in order, only the selected one unless downgrading is enabled
+    pub(crate) fn ec_levels(&self) -> impl Iterator<Item = EcLevel> + '_ {
+        [EcLevel::H, EcLevel::Q, EcLevel::M, EcLevel::L]
+            .into_iter()
+            .filter(|ec_level| {
+                *ec_level == self.ec_level || (self.auto_downgrade_ecc && *ec_level < self.ec_level)
+            })
+    }
+
+    /// Force a fixed QR-Code version, so that all generated codes have the same module count
+    ///
+    /// This is useful for printing labels of uniform size, e.g. together with [`EpcQr::with_amounts`](crate::EpcQr::with_amounts).
+    /// If the payload doesn't fit the version generation fails with [`GenerationError::PayloadTooLargeForVersion`].
+    #[must_use]
+    pub fn with_version(mut self, version: Option<Version>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Limit the QR-Code version of generated images
+    ///
+    /// Some printers and scanners struggle with dense codes, generating an image for a payload
+    /// that needs a higher version fails with [`GenerationError::VersionTooHigh`].
+    /// Shortening the fields or lowering the error correction level reduces the needed version.
+    #[must_use]
+    pub fn with_max_version(mut self, max_version: Option<u8>) -> Self {
+        self.max_version = max_version;
+        self
+    }
+
+    /// Set the edge length of a module in pixels, the default is 8
+    #[must_use]
+    pub fn with_module_size(mut self, module_size: u32) -> Self {
+        self.module_size = module_size.max(1);
+        self
+    }
+
+    /// Set the width of the quiet zone on each side in modules, the default is 4 modules on every side
+    ///
+    /// This allows leaving out the quiet zone on sides where the code borders an empty area anyway.
+    /// Scanners rely on the quiet zone to find the code, reducing or removing it risks codes that can't be scanned.
+    #[must_use]
+    pub fn with_quiet_zone_sides(mut self, top: u32, right: u32, bottom: u32, left: u32) -> Self {
+        self.quiet_zone = QuietZone {
+            top,
+            right,
+            bottom,
+            left,
+        };
+        self
+    }
+
+    /// Read generated image files back and compare every module against the encoded data,
+    /// failing with [`GenerationError::VerificationFailed`] on a mismatch
+    ///
+    /// This catches rendering problems as well as lossy formats degrading the code too much.
+    /// With the `decode` feature the image is also read by a full QR-Code decoder and the decoded payload
+    /// compared against the encoded one, failing with [`GenerationError::DecodingFailed`] on a mismatch.
+    #[must_use]
+    pub fn with_verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Set the colors of the dark and light modules as RGB, the default is black on white
+    ///
+    /// Many scanners expect dark modules on a light background and need a strong contrast between both,
+    /// see [`parse_color`] for parsing colors from hex strings.
+    ///
+    /// JPEG images are encoded without chroma subsampling (4:4:4) by default, so the colors don't bleed across
+    /// module boundaries, see [`RenderOptions::with_chroma_subsampling`]. The lossy compression still blurs
+    /// them slightly, consider [`RenderOptions::with_verify`].
+    #[must_use]
+    pub fn with_colors(mut self, dark: [u8; 3], light: [u8; 3]) -> Self {
+        self.dark = dark;
+        self.light = light;
+        self
+    }
+
+    /// Set the brightness of the dark and light modules for grayscale output, the default is 0 and 255
+    ///
+    /// Some thermal and label printers render a near-black like 30 better than pure black.
+    /// The images stay single channel grayscale where the format supports it.
+    /// This replaces colors set with [`RenderOptions::with_colors`].
+    #[must_use]
+    pub fn with_luma(self, dark: u8, light: u8) -> Self {
+        self.with_colors([dark; 3], [light; 3])
+    }
+
+    /// Set the shape used to draw dark modules, the default is [`ModuleShape::Square`]
+    ///
+    /// The finder patterns in the corners are always drawn square, as scanners rely on them to locate the code.
+    /// Shapes need a module size of at least 4 pixels, below that squares are drawn.
+    /// The gaps between dots and rounded modules make codes harder to scan, especially when printed small
+    /// or with low contrast, consider a higher error correction level when using them.
+    #[must_use]
+    pub fn with_module_shape(mut self, module_shape: ModuleShape) -> Self {
+        self.module_shape = module_shape;
+        self
+    }
+
+    /// Draw the dark modules of the three finder patterns in the corners in a distinct RGB color
+    ///
+    /// Rendering fails with [`GenerationError::InsufficientContrast`] if the color is too close to the light color.
+    /// Even then aggressive theming can make codes harder to scan, so test the result with several scanners.
+    #[must_use]
+    pub fn with_finder_color(mut self, finder: Option<[u8; 3]>) -> Self {
+        self.finder = finder;
+        self
+    }
+
+    /// Fill the quiet zone with a distinct RGB color instead of the light color, e.g. to blend into a background
+    ///
+    /// Scanners need the quiet zone to contrast with the dark modules just like the light modules do,
+    /// so the color should be about as light as the light color.
+    #[must_use]
+    pub fn with_quiet_zone_color(mut self, quiet_zone_color: Option<[u8; 3]>) -> Self {
+        self.quiet_zone_color = quiet_zone_color;
+        self
+    }
+
+    /// Surround the image with a solid frame of `width` pixels in the given RGB color, e.g. for marketing materials
+    ///
+    /// The frame is drawn outside the quiet zone and never replaces any of it,
+    /// so scanning is unaffected. A width of 0 removes the frame.
+    #[must_use]
+    pub fn with_frame(mut self, width: u32, color: [u8; 3]) -> Self {
+        self.frame = (0 < width).then_some((width, color));
+        self
+    }
+
+    /// Append the beneficiary, IBAN, amount and remittance as `<text>` lines below the code in SVG output
+    ///
+    /// Unlike text drawn into a raster image the caption stays crisp at any zoom and can be selected and searched,
+    /// e.g. for invoices embedded into web pages. The lines are those of [`EpcQr::human_summary`](crate::EpcQr::human_summary).
+    /// The SVG grows by the height of the caption, the dimensions reported by
+    /// [`EpcQr::qr_code_info`](crate::EpcQr::qr_code_info) don't include it. Raster images are unaffected.
+    #[must_use]
+    pub fn with_svg_caption(mut self, svg_caption: bool) -> Self {
+        self.svg_caption = svg_caption;
+        self
+    }
+
+    /// Scale the image to the given width in pixels, e.g. to fill a fixed slot in a layout
+    ///
+    /// The height is scaled by the same factor. Widths that are not an integer multiple of the modules
+    /// need a resize, see [`RenderOptions::with_resize_filter`]. Prefer [`RenderOptions::with_module_size`]
+    /// where the exact width doesn't matter, as it keeps every module the same size.
+    /// Scaling allocates a new image, so [`EpcQr::render_into`](crate::EpcQr::render_into) can't reuse its buffer then.
+    #[must_use]
+    pub fn with_target_width(mut self, target_width: Option<u32>) -> Self {
+        self.target_width = target_width;
+        self
+    }
+
+    /// Select the filter used to scale the image to the target width, the default is [`FilterType::Nearest`]
+    ///
+    /// Nearest-neighbor keeps the edges of the modules sharp and only uses the colors of the code,
+    /// which is what scanners expect. Other filters blur the edges and should only be used with large modules,
+    /// with the `tracing` feature a warning is logged when they are combined with modules smaller than 4 pixels.
+    #[must_use]
+    pub fn with_resize_filter(mut self, resize_filter: FilterType) -> Self {
+        self.resize_filter = resize_filter;
+        self
+    }
+
+    /// Scale `image` to the target width, if any
+    fn resize<P: image::Pixel + 'static>(
+        &self,
+        image: ImageBuffer<P, Vec<P::Subpixel>>,
+    ) -> ImageBuffer<P, Vec<P::Subpixel>> {
+        let (width, height) = image.dimensions();
+        let Some(target_width) = self.target_width.filter(|target| *target != width) else {
+            return image;
+        };
+        let target_height = scale_length(height, width, target_width);
+
+        #[cfg(feature = "tracing")]
+        {
+            let module_size = self.module_size as f32 * target_width as f32 / width as f32;
+            if self.resize_filter != FilterType::Nearest && module_size < 4.0 {
+                tracing::warn!(
+                    module_size,
+                    filter = ?self.resize_filter,
+                    "resizing small modules with a smoothing filter blurs them, which makes the code harder to scan"
+                );
+            }
+        }
+
+        image::imageops::resize(&image, target_width, target_height, self.resize_filter)
+    }
+
+    /// Center the image on a square canvas of the given edge length in pixels, filled with the quiet zone color
+    ///
+    /// This gives every code of a batch the same dimensions regardless of its QR-Code version,
+    /// e.g. to align them in a grid. The canvas is applied last, after the frame and scaling to the target width.
+    /// Rendering fails with [`GenerationError::CanvasTooSmall`] if the image doesn't fit.
+    /// SVG output ignores the canvas.
+    #[must_use]
+    pub fn with_canvas_size(mut self, canvas_size: Option<u32>) -> Self {
+        self.canvas_size = canvas_size;
+        self
+    }
+
+    /// Rotate the finished image clockwise, e.g. for label printers feeding the media sideways
+    ///
+    /// Scanners read QR-Codes in any orientation, so this only changes the physical layout.
+    /// The rotation is applied last, after the canvas, and swaps the width and height for 90 and 270 degrees.
+    /// SVG output ignores the rotation.
+    #[must_use]
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Center `image` on the canvas filled with `background`, if any
+    fn place_on_canvas<P: image::Pixel>(
+        &self,
+        image: ImageBuffer<P, Vec<P::Subpixel>>,
+        background: P,
+    ) -> Result<ImageBuffer<P, Vec<P::Subpixel>>, GenerationError> {
+        let Some(canvas_size) = self.canvas_size else {
+            return Ok(image);
+        };
+        let (width, height) = image.dimensions();
+        if canvas_size < width || canvas_size < height {
+            return Err(GenerationError::CanvasTooSmall {
+                width,
+                height,
+                canvas_size,
+            });
+        }
+        let mut canvas = ImageBuffer::from_pixel(canvas_size, canvas_size, background);
+        image::imageops::replace(
+            &mut canvas,
+            &image,
+            i64::from((canvas_size - width) / 2),
+            i64::from((canvas_size - height) / 2),
+        );
+        Ok(canvas)
+    }
+
+    /// The width of the frame in pixels, 0 without a frame
+    fn frame_width(&self) -> u32 {
+        self.frame.map_or(0, |(width, _)| width)
+    }
+
+    /// Call `put` for every pixel of the frame in an image of the given dimensions
+    fn draw_frame(&self, width: u32, height: u32, mut put: impl FnMut(u32, u32)) {
+        let frame = self.frame_width();
+        for y in 0..height {
+            for x in 0..width {
+                if x < frame || y < frame || width - frame <= x || height - frame <= y {
+                    put(x, y);
+                }
+            }
+        }
+    }
+
+    /// The pixel used for the frame, see [`RenderOptions::with_frame`]
+    fn frame_px(&self) -> Px {
+        let [r, g, b] = self.frame.map_or(self.light, |(_, color)| color);
+        Px(Rgba([r, g, b, 255]))
+    }
+
+    /// The pixel used for modules of the given color
+    fn px(&self, color: qrcode::Color) -> Px {
+        let [r, g, b] = color.select(self.dark, self.light);
+        Px(Rgba([r, g, b, 255]))
+    }
+
+    /// The pixel used for the quiet zone
+    fn quiet_zone_px(&self) -> Px {
+        match self.quiet_zone_color {
+            Some([r, g, b]) => Px(Rgba([r, g, b, 255])),
+            None => self.px(qrcode::Color::Light),
+        }
+    }
+
+    /// The pixel used for the dark modules of the finder patterns
+    fn finder_px(&self) -> Px {
+        match self.finder {
+            Some([r, g, b]) => Px(Rgba([r, g, b, 255])),
+            None => self.px(qrcode::Color::Dark),
+        }
+    }
+
+    /// Fail if the dark or finder color is too close to the light color to be told apart by scanners
+    fn check_contrast(&self) -> Result<(), GenerationError> {
+        let luma = |px: Px| image::Pixel::to_luma(&px.0).0[0];
+        let light = luma(self.px(qrcode::Color::Light));
+        for (color, px) in [
+            (self.dark, self.px(qrcode::Color::Dark)),
+            (self.finder.unwrap_or(self.dark), self.finder_px()),
+        ] {
+            if luma(px).abs_diff(light) < MIN_CONTRAST {
+                return Err(GenerationError::InsufficientContrast { color });
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw light gray lines between the modules, to inspect the module grid and alignment
+    ///
+    /// **Debug only:** the lines cut into the dark modules, the resulting images are not meant to be scanned.
+    /// The lines are 1 pixel wide, so this works best with a large module size.
+    #[must_use]
+    pub fn with_debug_grid(mut self, debug_grid: bool) -> Self {
+        self.debug_grid = debug_grid;
+        self
+    }
+
+    /// Encode PNG images with pinned encoder settings, so that the same code always produces the same bytes
+    ///
+    /// No optional metadata like timestamps (`tIME`), text (`tEXt`) or physical dimensions (`pHYs`) is written
+    /// either way, but the default settings of the `image` crate may change between its versions.
+    /// This is useful for content-addressed caching or diffing generated images in CI pipelines.
+    #[must_use]
+    pub fn with_reproducible(mut self, reproducible: bool) -> Self {
+        self.reproducible = reproducible;
+        self
+    }
+
+    /// Set the quality of JPEG images from 1 to 100, the default is 75
+    ///
+    /// Values outside of this range are clamped. Lower qualities blur the module edges more,
+    /// consider [`RenderOptions::with_verify`] when going below the default.
+    #[must_use]
+    pub fn with_jpeg_quality(mut self, quality: u8) -> Self {
+        self.jpeg_quality = quality.clamp(1, 100);
+        self
+    }
+
+    /// Set the chroma subsampling of colored JPEG images, the default is none (4:4:4)
+    ///
+    /// Subsampling stores the colors at a lower resolution than the brightness, which blurs the boundaries
+    /// between dark and light modules of colored codes and can break scanning. Grayscale images are not affected.
+    #[must_use]
+    pub fn with_chroma_subsampling(mut self, chroma_subsampling: ChromaSubsampling) -> Self {
+        self.chroma_subsampling = chroma_subsampling;
+        self
+    }
+
+    /// The dimensions in pixels of the image rendered for `code`, the canvas if any, after the rotation
+    pub(crate) fn image_dimensions(&self, code: &QrCode) -> (u32, u32) {
+        let dimensions = match self.canvas_size {
+            Some(canvas_size) => (canvas_size, canvas_size),
+            None => self.scaled_dimensions(code),
+        };
+        self.rotation.dimensions(dimensions)
+    }
+
+    /// The dimensions in pixels of the image rendered for `code`, after scaling it to the target width
+    fn scaled_dimensions(&self, code: &QrCode) -> (u32, u32) {
+        let (width, height) = self.unscaled_dimensions(code);
+        match self.target_width {
+            Some(target_width) => (target_width, scale_length(height, width, target_width)),
+            None => (width, height),
+        }
+    }
+
+    /// The dimensions in pixels of the image rendered for `code`, before scaling it to the target width
+    fn unscaled_dimensions(&self, code: &QrCode) -> (u32, u32) {
+        let modules = code.width() as u32;
+        let quiet_zone = &self.quiet_zone;
+        let frame = 2 * self.frame_width();
+        (
+            (modules + quiet_zone.left + quiet_zone.right) * self.module_size + frame,
+            (modules + quiet_zone.top + quiet_zone.bottom) * self.module_size + frame,
+        )
+    }
+
+    /// Fail for rendering choices banks are known to have problems with,
+    /// see [`EpcQr::with_strict_compatibility`](crate::EpcQr::with_strict_compatibility)
+    pub(crate) fn check_strict_compatibility(
+        &self,
+        format: Option<image::ImageFormat>,
+    ) -> Result<(), GenerationError> {
+        if format == Some(image::ImageFormat::Jpeg) {
+            return Err(GenerationError::StrictCompatibility(
+                "lossy image formats like JPEG are not allowed",
+            ));
+        }
+        let QuietZone {
+            top,
+            right,
+            bottom,
+            left,
+        } = self.quiet_zone;
+        if [top, right, bottom, left].into_iter().any(|side| side < 4) {
+            return Err(GenerationError::StrictCompatibility(
+                "the quiet zone must be at least 4 modules wide on every side",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fail if `code` exceeds the maximal allowed version
+    pub(crate) fn check_version(&self, code: &QrCode) -> Result<(), GenerationError> {
+        if let (Some(max), Version::Normal(got)) = (self.max_version, code.version()) {
+            if i16::from(max) < got {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(got, max, "QR-Code version too high");
+                return Err(GenerationError::VersionTooHigh { got, max });
+            }
+        }
+        Ok(())
+    }
+
+    /// Render `code` into an image, including the quiet zone
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub(crate) fn render(&self, code: &QrCode) -> Result<Image, GenerationError> {
+        self.check_version(code)?;
+        self.check_contrast()?;
+
+        #[cfg(feature = "tracing")]
+        {
+            let (width, height) = self.image_dimensions(code);
+            tracing::debug!(
+                module_size = self.module_size,
+                width,
+                height,
+                debug_grid = self.debug_grid,
+                "rendering image"
+            );
+        }
+
+        let mut image = if self.module_shape == ModuleShape::Square && self.finder.is_none() {
+            code.render::<Px>()
+                .quiet_zone(false)
+                .module_dimensions(self.module_size, self.module_size)
+                .dark_color(self.px(qrcode::Color::Dark))
+                .light_color(self.px(qrcode::Color::Light))
+                .build()
+                .pad(&self.quiet_zone, self.module_size, self.quiet_zone_px())
+                .pad(&QuietZone::uniform(self.frame_width()), 1, self.frame_px())
+        } else {
+            let (width, height) = self.unscaled_dimensions(code);
+            let mut buffer = RgbaImage::from_pixel(width, height, self.quiet_zone_px().0);
+            let frame = self.frame_px().0;
+            self.draw_frame(width, height, |x, y| buffer.put_pixel(x, y, frame));
+            let light = self.px(qrcode::Color::Light).0;
+            self.fill_code_area(code, |x, y| buffer.put_pixel(x, y, light));
+            let (dark, finder) = (self.px(qrcode::Color::Dark).0, self.finder_px().0);
+            self.draw_dark_modules(code, |x, y, in_finder| {
+                buffer.put_pixel(x, y, if in_finder { finder } else { dark })
+            });
+            Image::from_rgba(buffer)
+        };
+        if self.debug_grid {
+            image.draw_grid(self.module_size, self.frame_width());
+        }
+        image.buffer = self.place_on_canvas(self.resize(image.buffer), self.quiet_zone_px().0)?;
+        image.buffer = self.rotation.apply(image.buffer);
+        image.reproducible = self.reproducible;
+        image.jpeg_quality = self.jpeg_quality;
+        image.chroma_subsampling = self.chroma_subsampling;
+        Ok(image)
+    }
+
+    /// Render a gray stand-in shaped like a QR-Code onto a square canvas of `size` pixels
+    ///
+    /// The modules of a version 3 code are drawn with the quiet zone and frame of the options,
+    /// as large as fit onto the canvas. Smaller sizes are raised to fit 1 pixel per module. Besides the finder patterns, they follow a fixed pseudo-random pattern.
+    pub(crate) fn render_placeholder(&self, size: u32) -> RgbaImage {
+        let modules = PLACEHOLDER_MODULES;
+        let quiet_zone = &self.quiet_zone;
+        let frame = self.frame_width();
+        let extent = (modules + quiet_zone.left + quiet_zone.right)
+            .max(modules + quiet_zone.top + quiet_zone.bottom);
+        // at least 1 pixel per module, so that everything fits onto the canvas without shrinking
+        let size = size.max(extent + 2 * frame);
+        let module_size = (size - 2 * frame) / extent;
+        let options = Self {
+            module_size,
+            canvas_size: Some(size),
+            ..self.clone()
+        };
+
+        let width = (modules + quiet_zone.left + quiet_zone.right) * module_size + 2 * frame;
+        let height = (modules + quiet_zone.top + quiet_zone.bottom) * module_size + 2 * frame;
+        let mut buffer = RgbaImage::from_pixel(width, height, options.quiet_zone_px().0);
+        let frame_px = options.frame_px().0;
+        options.draw_frame(width, height, |x, y| buffer.put_pixel(x, y, frame_px));
+        let (light, gray) = (options.px(qrcode::Color::Light).0, Rgba(PLACEHOLDER_GRAY));
+        for y in 0..modules {
+            for x in 0..modules {
+                let px = if placeholder_module(x as usize, y as usize, modules as usize) {
+                    gray
+                } else {
+                    light
+                };
+                let left = (x + quiet_zone.left) * module_size + frame;
+                let top = (y + quiet_zone.top) * module_size + frame;
+                for dy in 0..module_size {
+                    for dx in 0..module_size {
+                        buffer.put_pixel(left + dx, top + dy, px);
+                    }
+                }
+            }
+        }
+
+        options
+            .place_on_canvas(buffer, options.quiet_zone_px().0)
+            .expect("the canvas is at least as large as the image")
+    }
+
+    /// Render `code` into `buffer` including the quiet zone, resizing it while keeping its allocation
+    pub(crate) fn render_into(
+        &self,
+        code: &QrCode,
+        buffer: &mut GrayImage,
+    ) -> Result<(), GenerationError> {
+        self.check_version(code)?;
+        self.check_contrast()?;
+
+        let (width, height) = self.unscaled_dimensions(code);
+        let luma = |color| image::Pixel::to_luma(&self.px(color).0);
+        let mut raw = std::mem::take(buffer).into_raw();
+        raw.clear();
+        raw.resize(
+            width as usize * height as usize,
+            image::Pixel::to_luma(&self.quiet_zone_px().0).0[0],
+        );
+        *buffer = GrayImage::from_raw(width, height, raw)
+            .expect("the buffer was resized to fit the dimensions");
+
+        let frame = image::Pixel::to_luma(&self.frame_px().0);
+        self.draw_frame(width, height, |x, y| buffer.put_pixel(x, y, frame));
+
+        let light = luma(qrcode::Color::Light);
+        self.fill_code_area(code, |x, y| buffer.put_pixel(x, y, light));
+
+        let dark = luma(qrcode::Color::Dark);
+        let finder = image::Pixel::to_luma(&self.finder_px().0);
+        self.draw_dark_modules(code, |x, y, in_finder| {
+            buffer.put_pixel(x, y, if in_finder { finder } else { dark })
+        });
+
+        let (module_size, frame) = (self.module_size, self.frame_width());
+        if self.debug_grid && 1 < module_size {
+            for (x, y, px) in buffer.enumerate_pixels_mut() {
+                if on_grid(x, y, module_size, frame, (width, height)) {
+                    *px = Luma([GRID_LUMA]);
+                }
+            }
+        }
+
+        if self
+            .target_width
+            .is_some_and(|target_width| target_width != width)
+        {
+            *buffer = self.resize(std::mem::take(buffer));
+        }
+        if self.canvas_size.is_some() {
+            let background = image::Pixel::to_luma(&self.quiet_zone_px().0);
+            *buffer = self.place_on_canvas(std::mem::take(buffer), background)?;
+        }
+        if self.rotation != Rotation::None {
+            *buffer = self.rotation.apply(std::mem::take(buffer));
+        }
+
+        Ok(())
+    }
+
+    /// Call `put` for every pixel inside the quiet zone
+    fn fill_code_area(&self, code: &QrCode, mut put: impl FnMut(u32, u32)) {
+        let size = code.width() as u32 * self.module_size;
+        let left = self.quiet_zone.left * self.module_size + self.frame_width();
+        let top = self.quiet_zone.top * self.module_size + self.frame_width();
+        for y in top..top + size {
+            for x in left..left + size {
+                put(x, y);
+            }
+        }
+    }
+
+    /// Call `put` for every pixel covered by a dark module, including the offset of the quiet zone,
+    /// and whether the module is part of a finder pattern
+    fn draw_dark_modules(&self, code: &QrCode, mut put: impl FnMut(u32, u32, bool)) {
+        let modules = code.width();
+        let module_size = self.module_size;
+        let frame = self.frame_width();
+        let shape = if module_size < 4 {
+            ModuleShape::Square
+        } else {
+            self.module_shape
+        };
+
+        for (idx, color) in code.to_colors().into_iter().enumerate() {
+            if color == qrcode::Color::Light {
+                continue;
+            }
+            let (x, y) = (idx % modules, idx / modules);
+            let finder = in_finder(x, y, modules);
+            let shape = if finder { ModuleShape::Square } else { shape };
+
+            let left = (x as u32 + self.quiet_zone.left) * module_size + frame;
+            let top = (y as u32 + self.quiet_zone.top) * module_size + frame;
+            for dy in 0..module_size {
+                for dx in 0..module_size {
+                    if shape.covers(dx, dy, module_size) {
+                        put(left + dx, top + dy, finder);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Render `code` as an SVG document, including the quiet zone, the frame and the `caption` lines below
+    ///
+    /// The dark modules are merged into horizontal runs of a single path, one per color,
+    /// which keeps the document small even for dense codes. The module shape is ignored, all modules are square.
+    pub(crate) fn render_svg(
+        &self,
+        code: &QrCode,
+        caption: &[String],
+    ) -> Result<String, GenerationError> {
+        self.check_version(code)?;
+        self.check_contrast()?;
+
+        let (width, height) = self.unscaled_dimensions(code);
+        let (module_size, frame) = (self.module_size, self.frame_width());
+        let modules = code.width();
+        let hex = |Px(Rgba([r, g, b, _])): Px| format!("#{r:02x}{g:02x}{b:02x}");
+
+        let font_size = 2 * module_size;
+        let line_height = font_size * 5 / 4;
+        let caption_height = match caption.len() as u32 {
+            0 => 0,
+            lines => lines * line_height + font_size / 2,
+        };
+        let total_height = height + caption_height;
+        // the target width only scales the document, the drawing keeps its unscaled coordinates
+        let scaled_width = self.target_width.unwrap_or(width);
+        let scaled_height = scale_length(total_height, width, scaled_width);
+
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{scaled_width}" height="{scaled_height}" viewBox="0 0 {width} {total_height}">"#
+        );
+        if 0 < caption_height {
+            svg.push_str(&format!(
+                r#"<rect y="{height}" width="{width}" height="{caption_height}" fill="{}"/>"#,
+                hex(self.quiet_zone_px())
+            ));
+        }
+        if 0 < frame {
+            svg.push_str(&format!(
+                r#"<rect width="{width}" height="{height}" fill="{}"/>"#,
+                hex(self.frame_px())
+            ));
+        }
+        svg.push_str(&format!(
+            r#"<rect x="{frame}" y="{frame}" width="{}" height="{}" fill="{}"/>"#,
+            width - 2 * frame,
+            height - 2 * frame,
+            hex(self.quiet_zone_px())
+        ));
+        let size = modules as u32 * module_size;
+        let (left, top) = (
+            self.quiet_zone.left * module_size + frame,
+            self.quiet_zone.top * module_size + frame,
+        );
+        svg.push_str(&format!(
+            r#"<rect x="{left}" y="{top}" width="{size}" height="{size}" fill="{}"/>"#,
+            hex(self.px(qrcode::Color::Light))
+        ));
+
+        // the finder patterns only need their own path with a distinct color
+        let mut paths = [String::new(), String::new()];
+        let colors = code.to_colors();
+        for (y, row) in colors.chunks(modules).enumerate() {
+            let mut run: Option<(usize, bool)> = None;
+            for x in 0..=modules {
+                let module = row
+                    .get(x)
+                    .filter(|color| **color == qrcode::Color::Dark)
+                    .map(|_| self.finder.is_some() && in_finder(x, y, modules));
+                if run.map(|(_, finder)| finder) == module {
+                    continue;
+                }
+                if let Some((start, finder)) = run.take() {
+                    let length = (x - start) as u32 * module_size;
+                    paths[usize::from(finder)].push_str(&format!(
+                        "M{} {}h{length}v{module_size}h-{length}z",
+                        left + start as u32 * module_size,
+                        top + y as u32 * module_size,
+                    ));
+                }
+                run = module.map(|finder| (x, finder));
+            }
+        }
+        for (path, px) in paths
+            .iter()
+            .zip([self.px(qrcode::Color::Dark), self.finder_px()])
+        {
+            if !path.is_empty() {
+                svg.push_str(&format!(r#"<path d="{path}" fill="{}"/>"#, hex(px)));
+            }
+        }
+
+        for (line, text) in caption.iter().enumerate() {
+            svg.push_str(&format!(
+                r#"<text x="{left}" y="{}" font-family="sans-serif" font-size="{font_size}" fill="{}">{}</text>"#,
+                height + (line as u32 + 1) * line_height,
+                hex(self.px(qrcode::Color::Dark)),
+                escape_xml(text)
+            ));
+        }
+
+        svg.push_str("</svg>");
+        Ok(svg)
+    }
+
+    /// Read back an image file rendered from `code` and compare its modules against the code
+    /// and, with the `decode` feature, its decoded payload against `data`
+    pub(crate) fn verify_image_file(
+        &self,
+        code: &QrCode,
+        data: &[u8],
+        file_path: &Path,
+    ) -> Result<(), GenerationError> {
+        let quiet_zone = &self.quiet_zone;
+        let image = image::io::Reader::open(file_path)?
+            .with_guessed_format()?
+            .decode()?
+            .into_luma8();
+        let image = self.rotation.inverse().apply(image);
+
+        let luma = |color| image::Pixel::to_luma(&self.px(color).0).0[0];
+        let (dark, light) = (luma(qrcode::Color::Dark), luma(qrcode::Color::Light));
+
+        let width = code.width() as u32;
+        let (module_size, frame) = (self.module_size, self.frame_width());
+        // the image may have been scaled to a target width and placed on a canvas
+        let (scaled_width, scaled_height) = self.scaled_dimensions(code);
+        let (padding_x, padding_y) = self.canvas_size.map_or((0, 0), |canvas_size| {
+            (
+                canvas_size.saturating_sub(scaled_width),
+                canvas_size.saturating_sub(scaled_height),
+            )
+        });
+        let code_width = image.width().saturating_sub(padding_x);
+        let (unscaled_width, unscaled_height) = self.unscaled_dimensions(code);
+        let scale = code_width as f32 / unscaled_width as f32;
+        let expected_height = scale_length(unscaled_height, unscaled_width, code_width) + padding_y;
+        if code_width == 0 || image.height() != expected_height {
+            return Err(GenerationError::VerificationFailed {
+                mismatched_modules: code.to_colors().len(),
+            });
+        }
+
+        let mismatched_modules = code
+            .to_colors()
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, color)| {
+                let (x, y) = (*idx as u32 % width, *idx as u32 / width);
+                let center = |module: u32, offset: u32, padding: u32| {
+                    let unscaled = (module + offset) * module_size + module_size / 2 + frame;
+                    (unscaled as f32 * scale) as u32 + padding / 2
+                };
+                let luma = image
+                    .get_pixel(
+                        center(x, quiet_zone.left, padding_x),
+                        center(y, quiet_zone.top, padding_y),
+                    )
+                    .0[0];
+                let read_dark = luma.abs_diff(dark) < luma.abs_diff(light);
+                read_dark != (*color == qrcode::Color::Dark)
+            })
+            .count();
+
+        if mismatched_modules != 0 {
+            return Err(GenerationError::VerificationFailed { mismatched_modules });
+        }
+
+        #[cfg(feature = "decode")]
+        {
+            let (width, height) = (image.width() as usize, image.height() as usize);
+            let mut prepared = rqrr::PreparedImage::prepare_from_bitmap(width, height, |x, y| {
+                let luma = image.get_pixel(x as u32, y as u32).0[0];
+                luma.abs_diff(dark) < luma.abs_diff(light)
+            });
+            let grid = prepared.detect_grids().into_iter().next().ok_or_else(|| {
+                GenerationError::DecodingFailed("no QR-Code found in the image".to_owned())
+            })?;
+            let mut decoded = Vec::new();
+            grid.decode_to(&mut decoded)
+                .map_err(|err| GenerationError::DecodingFailed(err.to_string()))?;
+            if decoded != data {
+                return Err(GenerationError::DecodingFailed(
+                    "the decoded payload differs".to_owned(),
+                ));
+            }
+        }
+        #[cfg(not(feature = "decode"))]
+        let _ = data;
+
+        Ok(())
+    }
+}
+
+/// Clockwise rotation of the image, see [`RenderOptions::with_rotation`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Rotation {
+    /// Keep the upright orientation
+    #[default]
+    #[cfg_attr(feature = "cli", value(name = "0"))]
+    None,
+    /// A quarter turn clockwise
+    #[cfg_attr(feature = "cli", value(name = "90"))]
+    R90,
+    /// Upside down
+    #[cfg_attr(feature = "cli", value(name = "180"))]
+    R180,
+    /// A quarter turn counterclockwise
+    #[cfg_attr(feature = "cli", value(name = "270"))]
+    R270,
+}
+
+impl Rotation {
+    /// The rotation undoing this one
+    fn inverse(self) -> Self {
+        match self {
+            Rotation::None => Rotation::None,
+            Rotation::R90 => Rotation::R270,
+            Rotation::R180 => Rotation::R180,
+            Rotation::R270 => Rotation::R90,
+        }
+    }
+
+    /// The dimensions of an image of `width` and `height` after the rotation
+    fn dimensions(self, (width, height): (u32, u32)) -> (u32, u32) {
+        match self {
+            Rotation::None | Rotation::R180 => (width, height),
+            Rotation::R90 | Rotation::R270 => (height, width),
+        }
+    }
+
+    /// Rotate `image` clockwise
+    fn apply<P: image::Pixel + 'static>(
+        self,
+        image: ImageBuffer<P, Vec<P::Subpixel>>,
+    ) -> ImageBuffer<P, Vec<P::Subpixel>> {
+        match self {
+            Rotation::None => image,
+            Rotation::R90 => image::imageops::rotate90(&image),
+            Rotation::R180 => image::imageops::rotate180(&image),
+            Rotation::R270 => image::imageops::rotate270(&image),
+        }
+    }
+}
+
+/// Resolution of the colors in JPEG images relative to the brightness, see [`RenderOptions::with_chroma_subsampling`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ChromaSubsampling {
+    /// Keep the full color resolution (4:4:4)
+    #[default]
+    #[cfg_attr(feature = "cli", value(name = "444"))]
+    None,
+    /// Halve the horizontal color resolution (4:2:2)
+    #[cfg_attr(feature = "cli", value(name = "422"))]
+    Horizontal,
+    /// Halve the horizontal and vertical color resolution (4:2:0), as most photos are stored
+    #[cfg_attr(feature = "cli", value(name = "420"))]
+    Both,
+}
+
+impl ChromaSubsampling {
+    /// The sampling factors of the encoder
+    fn sampling_factor(self) -> SamplingFactor {
+        match self {
+            ChromaSubsampling::None => SamplingFactor::R_4_4_4,
+            ChromaSubsampling::Horizontal => SamplingFactor::R_4_2_2,
+            ChromaSubsampling::Both => SamplingFactor::R_4_2_0,
+        }
+    }
+}
+
+/// The shape of dark modules, see [`RenderOptions::with_module_shape`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ModuleShape {
+    /// Filled squares, as defined by the QR-Code specification
+    #[default]
+    Square,
+    /// Circles touching the edges of the module
+    Dot,
+    /// Squares with rounded corners
+    Rounded,
+}
+
+impl ModuleShape {
+    /// Whether the pixel at `x`, `y` inside a module of `size` pixels is part of the shape
+    fn covers(self, x: u32, y: u32, size: u32) -> bool {
+        // offsets of the pixel center from the module center, doubled to stay integral
+        let size = i64::from(size);
+        let dx = (2 * i64::from(x) + 1 - size).abs();
+        let dy = (2 * i64::from(y) + 1 - size).abs();
+        match self {
+            ModuleShape::Square => true,
+            ModuleShape::Dot => dx * dx + dy * dy <= size * size,
+            ModuleShape::Rounded => {
+                let radius = size / 2;
+                let straight = size - radius;
+                dx <= straight
+                    || dy <= straight
+                    || (dx - straight).pow(2) + (dy - straight).pow(2) <= radius * radius
+            }
+        }
+    }
+}
+
+/// Details about the QR-Code and image generated for a payload, see [`EpcQr::qr_code_info`]
+#[derive(Debug, Clone)]
+pub struct QrCodeInfo {
+    /// The QR-Code version
+    pub version: Version,
+    /// The error correction level
+    pub ec_level: EcLevel,
+    /// The number of modules along each edge, excluding the quiet zone
+    pub modules: usize,
+    /// The width of the rendered image in pixels
+    pub width: u32,
+    /// The height of the rendered image in pixels
+    pub height: u32,
+}
+
+/// Render several codes onto a single sheet, arranged in a grid with `cols` columns
+///
+/// Every code is centered in a cell as large as the largest code, with a caption of the beneficiary name
+/// and the amount (if any) below it. The quiet zones of the codes serve as margins between them
+/// and the sheet is filled with the quiet zone color. Rows are filled from left to right.
+///
+/// The caption is drawn in the dark color with a built-in 5x7 pixel font, scaled to a pixel per quarter module.
+/// Accented Latin letters are drawn without their accent, other characters beyond ASCII as `?`,
+/// and lines longer than the cell are cut off.
+pub fn generate_sheet<S: AsRef<str>>(
+    codes: &[EpcQr<S>],
+    cols: usize,
+    options: &RenderOptions,
+) -> Result<DynamicImage, GenerationError> {
+    let images = codes
+        .iter()
+        .map(|code| code.to_rgba_image(options))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let cols = cols.max(1);
+    let rows = images.len().div_ceil(cols);
+    let cell_width = images.iter().map(RgbaImage::width).max().unwrap_or(0);
+    let image_height = images.iter().map(RgbaImage::height).max().unwrap_or(0);
+    let scale = (options.module_size / 4).max(1);
+    let line_height = (font::GLYPH_HEIGHT + 2) * scale;
+    let cell_height = image_height + SHEET_CAPTION_LINES * line_height;
+    let max_chars = (cell_width / (font::ADVANCE * scale)) as usize;
+
+    let mut sheet = RgbaImage::from_pixel(
+        cell_width * cols as u32,
+        cell_height * rows as u32,
+        options.quiet_zone_px().0,
+    );
+    for (idx, (image, code)) in images.iter().zip(codes).enumerate() {
+        let (col, row) = ((idx % cols) as u32, (idx / cols) as u32);
+        let x = col * cell_width + (cell_width - image.width()) / 2;
+        let y = row * cell_height + (image_height - image.height()) / 2;
+        image::imageops::replace(&mut sheet, image, i64::from(x), i64::from(y));
+
+        let caption = [
+            Some(code.beneficiary_name().to_owned()),
+            code.amount().map(|amount| format!("EUR {amount}")),
+        ];
+        for (line, text) in caption.iter().enumerate() {
+            let Some(text) = text else { continue };
+            let text = text.chars().take(max_chars).collect::<String>();
+            let width = text.chars().count() as u32 * font::ADVANCE * scale;
+            let x = col * cell_width + (cell_width - width) / 2;
+            let y = row * cell_height + image_height + line as u32 * line_height + scale;
+            font::draw_text(
+                &mut sheet,
+                &text,
+                (x, y),
+                scale,
+                options.px(qrcode::Color::Dark).0,
+            );
+        }
+    }
+
+    Ok(DynamicImage::ImageRgba8(sheet))
+}
+
+pub(crate) struct Image {
+    buffer: RgbaImage,
+    /// Pin the PNG encoder settings, see [`RenderOptions::with_reproducible`]
+    reproducible: bool,
+    /// See [`RenderOptions::with_jpeg_quality`]
+    jpeg_quality: u8,
+    /// See [`RenderOptions::with_chroma_subsampling`]
+    chroma_subsampling: ChromaSubsampling,
+}
+
+impl Image {
+    pub fn from_rgba(buffer: RgbaImage) -> Self {
+        Self {
+            buffer,
+            reproducible: false,
+            jpeg_quality: DEFAULT_JPEG_QUALITY,
+            chroma_subsampling: ChromaSubsampling::None,
+        }
+    }
+
+    pub fn into_rgba(self) -> RgbaImage {
+        self.buffer
+    }
+
+    /// Convert into the most compact pixel type that can represent the image in `format`
+    ///
+    /// Opaque grayscale images are stored with a single channel, JPEG doesn't support transparency.
+    /// The GIF encoder only accepts RGBA, it builds a palette with just the colors used.
+    fn to_dynamic(&self, format: image::ImageFormat) -> DynamicImage {
+        if format == image::ImageFormat::Gif {
+            return DynamicImage::ImageRgba8(self.buffer.clone());
+        }
+        let opaque = self.buffer.pixels().all(|px| px.0[3] == 255);
+        let gray = opaque
+            && self
+                .buffer
+                .pixels()
+                .all(|px| px.0[0] == px.0[1] && px.0[1] == px.0[2]);
+        if gray {
+            DynamicImage::ImageLuma8(ImageBuffer::from_fn(
+                self.buffer.width(),
+                self.buffer.height(),
+                |x, y| Luma([self.buffer.get_pixel(x, y).0[0]]),
+            ))
+        } else if opaque || format == image::ImageFormat::Jpeg {
+            DynamicImage::ImageRgba8(self.buffer.clone())
+                .into_rgb8()
+                .into()
+        } else {
+            DynamicImage::ImageRgba8(self.buffer.clone())
+        }
+    }
+
+    pub fn encode(&self, format: ImageFormat) -> Result<Vec<u8>, GenerationError> {
+        match format {
+            ImageFormat::ImageFormat(image::ImageFormat::Png) if self.reproducible => {
+                let image = self.to_dynamic(image::ImageFormat::Png);
+                let mut data = Vec::new();
+                PngEncoder::new_with_quality(
+                    &mut data,
+                    CompressionType::Fast,
+                    PngFilterType::Adaptive,
+                )
+                .write_image(
+                    image.as_bytes(),
+                    image.width(),
+                    image.height(),
+                    image.color(),
+                )?;
+                Ok(data)
+            }
+            ImageFormat::ImageFormat(image::ImageFormat::Jpeg) => {
+                let image = self.to_dynamic(image::ImageFormat::Jpeg);
+                let (width, height) =
+                    match (u16::try_from(image.width()), u16::try_from(image.height())) {
+                        (Ok(width), Ok(height)) => (width, height),
+                        _ => {
+                            return Err(GenerationError::ImageError(ImageError::Limits(
+                                LimitError::from_kind(LimitErrorKind::DimensionError),
+                            )))
+                        }
+                    };
+                let color = match image {
+                    DynamicImage::ImageLuma8(_) => ColorType::Luma,
+                    _ => ColorType::Rgb,
+                };
+                let mut data = Vec::new();
+                let mut encoder = JpegEncoder::new(&mut data, self.jpeg_quality);
+                encoder.set_sampling_factor(self.chroma_subsampling.sampling_factor());
+                encoder.encode(image.as_bytes(), width, height, color)?;
+                Ok(data)
+            }
+            ImageFormat::ImageFormat(format) => {
+                let mut data = Cursor::new(Vec::new());
+                self.to_dynamic(format).write_to(&mut data, format)?;
+                Ok(data.into_inner())
+            }
+            #[cfg(feature = "qoi")]
+            ImageFormat::Qoi => Ok(arqoii::QoiEncoder::new(
+                QoiHeader::new(
+                    self.buffer.width(),
+                    self.buffer.height(),
+                    arqoii::types::QoiChannels::Rgb,
+                    arqoii::types::QoiColorSpace::SRgbWithLinearAlpha,
+                ),
+                self.buffer.pixels().map(|px| arqoii::Pixel {
+                    r: px.0[0],
+                    g: px.0[1],
+                    b: px.0[2],
+                    a: px.0[3],
+                }),
+            )
+            .collect::<Vec<_>>()),
+        }
+    }
+
+    pub fn save(&self, format: ImageFormat, file_path: &Path) -> Result<(), GenerationError> {
+        match format {
+            ImageFormat::ImageFormat(image::ImageFormat::Png) if self.reproducible => {
+                std::fs::write(file_path, self.encode(format)?)?;
+            }
+            ImageFormat::ImageFormat(image::ImageFormat::Jpeg) => {
+                std::fs::write(file_path, self.encode(format)?)?;
+            }
+            ImageFormat::ImageFormat(format) => {
+                self.to_dynamic(format)
+                    .save_with_format(file_path, format)?;
+            }
+            #[cfg(feature = "qoi")]
+            ImageFormat::Qoi => {
+                std::fs::write(file_path, self.encode(format)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Surround the image with a border of `light` pixels of the given width in modules on each side
+    fn pad(self, quiet_zone: &QuietZone, module_size: u32, light: Px) -> Self {
+        let mut buffer = ImageBuffer::from_pixel(
+            self.buffer.width() + (quiet_zone.left + quiet_zone.right) * module_size,
+            self.buffer.height() + (quiet_zone.top + quiet_zone.bottom) * module_size,
+            light.0,
+        );
+        image::imageops::replace(
+            &mut buffer,
+            &self.buffer,
+            i64::from(quiet_zone.left * module_size),
+            i64::from(quiet_zone.top * module_size),
+        );
+        Self { buffer, ..self }
+    }
+
+    /// Draw a 1 pixel light gray line along the top and left edge of every module inside the frame
+    fn draw_grid(&mut self, module_size: u32, frame: u32) {
+        if module_size < 2 {
+            return;
+        }
+        let dimensions = self.buffer.dimensions();
+        for (x, y, px) in self.buffer.enumerate_pixels_mut() {
+            if on_grid(x, y, module_size, frame, dimensions) {
+                *px = Rgba([GRID_LUMA, GRID_LUMA, GRID_LUMA, 255]);
+            }
+        }
+    }
+
+    pub fn save_guess_format(&self, file_path: &Path) -> Result<(), GenerationError> {
+        #[cfg(feature = "qoi")]
+        if file_path.extension().is_some_and(|ext| ext == "qoi") {
+            return self.save(ImageFormat::Qoi, file_path);
+        }
+        let format = image::ImageFormat::from_path(file_path)?;
+        self.save(ImageFormat::ImageFormat(format), file_path)
+    }
+}
+
+/// The minimal edge length of a module in millimeters to scan a printed code of `modules` modules per edge,
+/// excluding the quiet zone, from `scan_distance_cm` centimeters
+///
+/// This uses the common rule of thumb that a QR-Code can be scanned from about ten times its edge length,
+/// see [`EpcQr::recommended_min_size_mm`](crate::EpcQr::recommended_min_size_mm).
+/// The module count is [`QrCodeInfo::modules`], e.g. 21 for a Version 1 code.
+pub fn min_module_size_mm(scan_distance_cm: f32, modules: usize) -> f32 {
+    let scan_distance_mm = scan_distance_cm * 10.0;
+    scan_distance_mm / 10.0 / modules.max(1) as f32
+}
+
+/// The module size in pixels to use for a code of `modules` modules per edge printed at `dpi` dots per inch,
+/// so that it can be scanned from `scan_distance_cm` centimeters, see [`min_module_size_mm`]
+///
+/// The result is rounded up to whole pixels and at least 1.
+pub fn module_size_for_print(scan_distance_cm: f32, dpi: u32, modules: usize) -> u32 {
+    let module_size_inch = min_module_size_mm(scan_distance_cm, modules) / 25.4;
+    ((module_size_inch * dpi as f32).ceil() as u32).max(1)
+}
+
+/// Parse a color from a hex string as `#rrggbb`, `rrggbb` or `#rgb`
+///
+/// Malformed input fails with [`GenerationError::InvalidColor`].
+pub fn parse_color(input: &str) -> Result<[u8; 3], GenerationError> {
+    let invalid = || GenerationError::InvalidColor {
+        input: input.to_owned(),
+    };
+    let (short_allowed, hex) = match input.strip_prefix('#') {
+        Some(hex) => (true, hex),
+        None => (false, input),
+    };
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).map_err(|_| invalid());
+    match hex.len() {
+        6 => Ok([
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+        ]),
+        3 if short_allowed => Ok([
+            channel(&hex[0..1])? * 17,
+            channel(&hex[1..2])? * 17,
+            channel(&hex[2..3])? * 17,
+        ]),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse a frame as `<width>:<color>`, with the width in pixels and the color as accepted by [`parse_color`]
+///
+/// E.g. `4:000000` is a black frame of 4 pixels, see [`RenderOptions::with_frame`].
+/// Malformed input fails with [`GenerationError::InvalidFrame`].
+pub fn parse_frame(input: &str) -> Result<(u32, [u8; 3]), GenerationError> {
+    let invalid = || GenerationError::InvalidFrame {
+        input: input.to_owned(),
+    };
+    let (width, color) = input.split_once(':').ok_or_else(invalid)?;
+    let width = width.parse().map_err(|_| invalid())?;
+    let color = parse_color(color).map_err(|_| invalid())?;
+    Ok((width, color))
+}
+
+/// Scale `length` by `to / from`, rounded to the nearest pixel but at least 1
+fn scale_length(length: u32, from: u32, to: u32) -> u32 {
+    ((u64::from(length) * u64::from(to) + u64::from(from) / 2) / u64::from(from)).max(1) as u32
+}
+
+/// Escape the characters with a special meaning in XML text and attributes
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Whether the module at `x`, `y` is part of one of the three finder patterns of a code with `modules` modules
+fn in_finder(x: usize, y: usize, modules: usize) -> bool {
+    (x < 7 || modules - 7 <= x) && y < 7 || x < 7 && modules - 7 <= y
+}
+
+/// Whether the pixel at `x`, `y` lies on a line of the debug grid, which leaves out the frame
+fn on_grid(x: u32, y: u32, module_size: u32, frame: u32, (width, height): (u32, u32)) -> bool {
+    let inside = frame <= x && frame <= y && x < width - frame && y < height - frame;
+    inside && ((x - frame).is_multiple_of(module_size) || (y - frame).is_multiple_of(module_size))
+}
+
+/// Whether the module at `x`, `y` of a placeholder with `modules` modules is gray
+///
+/// The finder patterns are drawn as usual, separated by a light ring from the rest,
+/// which is hashed from the position to look like random data.
+fn placeholder_module(x: usize, y: usize, modules: usize) -> bool {
+    if in_finder(x, y, modules) {
+        let (dx, dy) = (x % (modules - 7), y % (modules - 7));
+        let ring = dx.abs_diff(3).max(dy.abs_diff(3));
+        return ring != 2;
+    }
+    let separator = (x < 8 || modules - 8 <= x) && y < 8 || x < 8 && modules - 8 <= y;
+    let hash = (x as u32).wrapping_mul(0x9E37_79B1) ^ (y as u32).wrapping_mul(0x85EB_CA77);
+    !separator && (hash ^ hash >> 15).wrapping_mul(0x2C1B_3C6D) >> 31 == 1
+}
+
+/// Number of modules per side of the placeholder, those of a version 3 code
+const PLACEHOLDER_MODULES: u32 = 29;
+
+/// Color of the modules of the placeholder, see [`EpcQr::placeholder_image`]
+const PLACEHOLDER_GRAY: [u8; 4] = [192, 192, 192, 255];
+
+/// Minimal difference in brightness between the light and the dark or finder color
+const MIN_CONTRAST: u8 = 100;
+
+/// Quality of JPEG images unless set with [`RenderOptions::with_jpeg_quality`], the default of the `image` crate
+const DEFAULT_JPEG_QUALITY: u8 = 75;
+
+/// Brightness of the lines drawn by [`RenderOptions::with_debug_grid`]
+const GRID_LUMA: u8 = 200;
+
+/// The number of caption lines below each code on a sheet, see [`generate_sheet`]
+const SHEET_CAPTION_LINES: u32 = 2;
+
+/// Width of the quiet zone around the code on each side in modules
+#[derive(Debug, Clone)]
+pub(crate) struct QuietZone {
+    top: u32,
+    right: u32,
+    bottom: u32,
+    left: u32,
+}
+
+impl QuietZone {
+    /// The same width on every side
+    fn uniform(width: u32) -> Self {
+        Self {
+            top: width,
+            right: width,
+            bottom: width,
+            left: width,
+        }
+    }
+}
+
+impl Default for QuietZone {
+    fn default() -> Self {
+        Self {
+            top: 4,
+            right: 4,
+            bottom: 4,
+            left: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Px(Rgba<u8>);
+
+struct Canvas(Px, Image);
+
+impl Pixel for Px {
+    type Image = Image;
+
+    type Canvas = Canvas;
+
+    fn default_color(color: qrcode::Color) -> Self {
+        let luma = color.select(0, 255);
+        Self(Rgba([luma, luma, luma, 255]))
+    }
+}
+
+impl qrcode::render::Canvas for Canvas {
+    type Pixel = Px;
+
+    type Image = <Px as Pixel>::Image;
+
+    fn new(width: u32, height: u32, dark_pixel: Self::Pixel, light_pixel: Self::Pixel) -> Self {
+        Self(
+            dark_pixel,
+            Image::from_rgba(ImageBuffer::from_pixel(width, height, light_pixel.0)),
+        )
+    }
+
+    fn draw_dark_pixel(&mut self, x: u32, y: u32) {
+        self.1.buffer.put_pixel(x, y, self.0 .0)
+    }
+
+    fn into_image(self) -> Self::Image {
+        self.1
+    }
+}
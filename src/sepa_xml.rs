@@ -0,0 +1,143 @@
+//! Create EPC QR-Codes from SEPA credit transfer XML (pain.001)
+//!
+//! Only the first credit transfer transaction (`CdtTrfTxInf`) of the document is used.
+//! Namespace prefixes are ignored, so fragments without the surrounding `Document` work as well.
+//! The elements map to the fields as follows:
+//!
+//! | Element                                  | Field                           |
+//! |------------------------------------------|---------------------------------|
+//! | `Cdtr/Nm`                                | beneficiary name (mandatory)    |
+//! | `CdtrAcct/Id/IBAN`                       | beneficiary account (mandatory) |
+//! | `CdtrAgt/FinInstnId/BIC` or `BICFI`      | BIC                             |
+//! | `Amt/InstdAmt`, currency must be `EUR`   | amount                          |
+//! | `Purp/Cd`                                | purpose                         |
+//! | `RmtInf/Strd/CdtrRefInf/Ref`             | remittance reference            |
+//! | `RmtInf/Ustrd`                           | remittance text                 |
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::{Amount, EpcQr, InvalidAmount, InvalidEpcCode, Remittance};
+
+impl EpcQr {
+    /// Extract the fields of the first credit transfer in a pain.001 XML document or fragment,
+    /// see the [module documentation](crate::sepa_xml) for the mapping of elements to fields
+    pub fn from_sepa_xml(xml: &str) -> Result<Self, SepaXmlError> {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let mut path = Vec::<String>::new();
+        let mut fields = Fields::default();
+        loop {
+            match reader.read_event()? {
+                Event::Start(element) => {
+                    let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+                    if name == "InstdAmt" {
+                        let currency = element
+                            .try_get_attribute("Ccy")?
+                            .map(|currency| currency.unescape_value())
+                            .transpose()?
+                            .unwrap_or_default();
+                        if currency != "EUR" {
+                            return Err(SepaXmlError::UnsupportedCurrency(currency.into_owned()));
+                        }
+                    }
+                    path.push(name);
+                }
+                Event::End(_) => {
+                    let element = path.pop();
+                    if element.as_deref() == Some("CdtTrfTxInf") {
+                        break;
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some(field) = fields.field(&path) {
+                        *field = Some(text.unescape()?.into_owned());
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        let name = fields.name.ok_or(SepaXmlError::MissingElement("Cdtr/Nm"))?;
+        let iban = fields
+            .iban
+            .ok_or(SepaXmlError::MissingElement("CdtrAcct/Id/IBAN"))?;
+        let amount = fields
+            .amount
+            .map(|amount| Amount::from_payload_str(&amount))
+            .transpose()?;
+        let remittance = match (fields.reference, fields.text) {
+            (Some(reference), None) => Some(Remittance::Reference(reference)),
+            (None, Some(text)) => Some(Remittance::Text(text)),
+            (None, None) => None,
+            (Some(_), Some(_)) => return Err(InvalidEpcCode::DuplicateRemittance.into()),
+        };
+
+        Ok(EpcQr::new(name, iban.replace(' ', ""))
+            .with_bic(fields.bic)
+            .with_amount(amount)
+            .with_purpose(fields.purpose)
+            .with_remittance(remittance))
+    }
+}
+
+/// The text of the elements relevant for an EPC QR-Code
+#[derive(Default)]
+struct Fields {
+    name: Option<String>,
+    iban: Option<String>,
+    bic: Option<String>,
+    amount: Option<String>,
+    purpose: Option<String>,
+    reference: Option<String>,
+    text: Option<String>,
+}
+
+impl Fields {
+    /// The field storing the text of the element at `path`, if it's relevant
+    fn field(&mut self, path: &[String]) -> Option<&mut Option<String>> {
+        let ends_with = |suffix: &[&str]| {
+            path.len() >= suffix.len()
+                && path[path.len() - suffix.len()..]
+                    .iter()
+                    .zip(suffix)
+                    .all(|(element, expected)| element == expected)
+        };
+
+        if ends_with(&["Cdtr", "Nm"]) {
+            Some(&mut self.name)
+        } else if ends_with(&["CdtrAcct", "Id", "IBAN"]) {
+            Some(&mut self.iban)
+        } else if ends_with(&["CdtrAgt", "FinInstnId", "BIC"])
+            || ends_with(&["CdtrAgt", "FinInstnId", "BICFI"])
+        {
+            Some(&mut self.bic)
+        } else if ends_with(&["Amt", "InstdAmt"]) {
+            Some(&mut self.amount)
+        } else if ends_with(&["Purp", "Cd"]) {
+            Some(&mut self.purpose)
+        } else if ends_with(&["Strd", "CdtrRefInf", "Ref"]) {
+            Some(&mut self.reference)
+        } else if ends_with(&["RmtInf", "Ustrd"]) {
+            Some(&mut self.text)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SepaXmlError {
+    #[error("{0}")]
+    Xml(#[from] quick_xml::Error),
+    #[error("Missing mandatory element {0}")]
+    MissingElement(&'static str),
+    #[error("Only amounts in EUR are supported, but the currency was '{0}'")]
+    UnsupportedCurrency(String),
+    #[error("{0}")]
+    InvalidAmount(#[from] InvalidAmount),
+    #[error("{0}")]
+    InvalidEpcCode(#[from] InvalidEpcCode),
+}
@@ -0,0 +1,32 @@
+//! Constants of the EPC QR-Code format as defined by the EPC guidelines (EPC069-12)
+//!
+//! The field lengths are in characters, the payload size in bytes after encoding in the selected character set.
+
+/// The service tag in the first line of every payload
+pub const SERVICE_TAG: &str = "BCD";
+/// The identification of SEPA credit transfers in the fourth line
+pub const SCT_IDENTIFICATION: &str = "SCT";
+/// The version line for [`EpcVersion::V1`](crate::EpcVersion::V1)
+pub const VERSION_1: &str = "001";
+/// The version line for [`EpcVersion::V2`](crate::EpcVersion::V2)
+pub const VERSION_2: &str = "002";
+
+/// The maximal size of the encoded payload
+pub const MAX_PAYLOAD_BYTES: usize = 331;
+/// The number of lines of a payload with all fields set
+pub const MAX_LINES: usize = 12;
+
+/// The allowed lengths of the BIC, with and without the branch code
+pub const BIC_LENGTHS: [usize; 2] = [8, 11];
+/// The maximal length of the beneficiary name
+pub const MAX_NAME_LENGTH: usize = 70;
+/// The maximal length of the beneficiary account, an IBAN
+pub const MAX_IBAN_LENGTH: usize = 34;
+/// The maximal length of the purpose code
+pub const MAX_PURPOSE_LENGTH: usize = 4;
+/// The maximal length of the structured remittance reference
+pub const MAX_REFERENCE_LENGTH: usize = 35;
+/// The maximal length of the unstructured remittance text
+pub const MAX_TEXT_LENGTH: usize = 140;
+/// The maximal length of the beneficiary to originator information
+pub const MAX_INFO_LENGTH: usize = 70;
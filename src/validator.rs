@@ -0,0 +1,98 @@
+//! Customizable validation of the fields of an EPC QR-Code
+//!
+//! [`EpcStandardValidator`] implements the rules of the EPC guidelines and is used unless a different
+//! validator is set with [`EpcQr::with_validator`](crate::EpcQr::with_validator).
+//! Banks often enforce stricter rules, e.g. shorter names or only ASCII characters,
+//! which can be checked with a custom [`Validator`].
+
+use std::fmt::Debug;
+
+use crate::{bic_is_valid, iban_is_valid, spec, Amount, EpcQrRef, FieldError, Remittance};
+
+/// Rules the fields of an EPC QR-Code must follow
+///
+/// The combined size of the payload is checked by [`EpcQr::validate`](crate::EpcQr::validate)
+/// independent of the validator.
+pub trait Validator: Debug + Send + Sync {
+    /// Check all fields of `code`, returning one [`FieldError`] per problem found
+    ///
+    /// An empty list means all fields are valid.
+    fn validate(&self, code: &EpcQrRef<'_>) -> Vec<FieldError>;
+}
+
+/// The field rules of the EPC guidelines, the default [`Validator`]
+///
+/// Custom validators can call this to only add rules on top of the standard ones.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EpcStandardValidator;
+
+impl Validator for EpcStandardValidator {
+    fn validate(&self, code: &EpcQrRef<'_>) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if let Some(bic) = &code.bic {
+            let length = bic.chars().count();
+            if !spec::BIC_LENGTHS.contains(&length) {
+                errors.push(FieldError::InvalidBicLength(length));
+            } else if !bic_is_valid(bic) {
+                errors.push(FieldError::InvalidBic);
+            }
+        } else if code.bic_required() {
+            errors.push(FieldError::MissingBic);
+        }
+
+        let length = code.normalized(code.beneficiary_name).chars().count();
+        if !(1..=spec::MAX_NAME_LENGTH).contains(&length) {
+            errors.push(FieldError::InvalidNameLength(length));
+        }
+
+        let account = code.normalized_account();
+        let length = account.chars().count();
+        if !(1..=spec::MAX_IBAN_LENGTH).contains(&length) {
+            errors.push(FieldError::InvalidIbanLength(length));
+        } else if !iban_is_valid(&account) {
+            errors.push(FieldError::InvalidIban);
+        }
+
+        if let Some(amount) = &code.amount {
+            if amount.euro == 0 && amount.cent == 0 {
+                errors.push(FieldError::ZeroAmount);
+            } else if !Amount::is_in_range(amount.euro, amount.cent) {
+                errors.push(FieldError::InvalidAmount {
+                    euro: amount.euro,
+                    cent: amount.cent,
+                });
+            }
+        }
+
+        if let Some(purpose) = code.normalized_purpose() {
+            let length = purpose.chars().count();
+            if !(1..=spec::MAX_PURPOSE_LENGTH).contains(&length) {
+                errors.push(FieldError::InvalidPurposeLength(length));
+            }
+        }
+
+        if let Some(error) = code
+            .normalized_remittance()
+            .as_ref()
+            .and_then(Remittance::length_error)
+        {
+            errors.push(error);
+        }
+
+        if let Some(info) = &code.info {
+            let length = code.normalized(info).chars().count();
+            if !(1..=spec::MAX_INFO_LENGTH).contains(&length) {
+                errors.push(FieldError::InvalidInfoLength(length));
+            }
+        }
+
+        for (index, field) in code.extra_fields.iter().enumerate() {
+            if field.contains(['\n', '\r']) {
+                errors.push(FieldError::InvalidExtraField(index));
+            }
+        }
+
+        errors
+    }
+}
@@ -0,0 +1,57 @@
+use epc_qr_code_generator::{Amount, CharacterSet, EpcQr, FromParts, Remittance};
+
+#[test]
+fn read_back_fields() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_bic(Some("COBADEFFXXX"))
+        .with_amount(Some(Amount::from_cents(1250).unwrap()))
+        .with_purpose(Some("GDDS"))
+        .with_remittance(Some(Remittance::Text("Invoice 42")))
+        .with_info(Some("Thanks"))
+        .with_character_set(CharacterSet::ISO8859_15);
+
+    assert_eq!(code.beneficiary_name(), "Max Mustermann");
+    assert_eq!(code.beneficiary_account(), "DE89370400440532013000");
+    assert_eq!(code.bic(), Some("COBADEFFXXX"));
+    assert_eq!(code.amount(), Some(&Amount::from_cents(1250).unwrap()));
+    assert_eq!(code.purpose(), Some("GDDS"));
+    assert!(matches!(
+        code.remittance(),
+        Some(Remittance::Text("Invoice 42"))
+    ));
+    assert_eq!(code.info(), Some("Thanks"));
+    assert_eq!(code.character_set(), &CharacterSet::ISO8859_15);
+
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    assert_eq!(code.bic(), None);
+    assert_eq!(code.amount(), None);
+    assert_eq!(code.purpose(), None);
+    assert!(code.remittance().is_none());
+    assert_eq!(code.info(), None);
+}
+
+#[test]
+fn from_parts() {
+    let code = EpcQr::from_parts(FromParts {
+        name: "Max Mustermann",
+        account: "DE89370400440532013000",
+        bic: Some("COBADEFFXXX"),
+        amount: Some(Amount::from_cents(1250).unwrap()),
+        purpose: Some("GDDS"),
+        remittance: Some(Remittance::Reference("RF18539007547034")),
+        info: Some("Thanks"),
+        charset: CharacterSet::ISO8859_15,
+    });
+
+    let built = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_bic(Some("COBADEFFXXX"))
+        .with_amount(Some(Amount::from_cents(1250).unwrap()))
+        .with_purpose(Some("GDDS"))
+        .with_remittance(Some(Remittance::Reference("RF18539007547034")))
+        .with_info(Some("Thanks"))
+        .with_character_set(CharacterSet::ISO8859_15);
+    assert_eq!(code, built);
+    assert_eq!(code.to_string(), built.to_string());
+    assert_eq!(code.character_set(), &CharacterSet::ISO8859_15);
+    assert!(code.is_valid());
+}
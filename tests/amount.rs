@@ -0,0 +1,125 @@
+use epc_qr_code_generator::{Amount, EpcQr, InvalidAmount, InvalidPayload, Remittance};
+
+#[test]
+fn from_str_honors_range() {
+    for bound in [Amount::MIN, Amount::MAX] {
+        let parsed = bound.to_string().parse::<Amount>().unwrap();
+        assert_eq!(parsed.to_string(), bound.to_string());
+    }
+    assert!("0.00".parse::<Amount>().is_err());
+    assert!("1000000000.00".parse::<Amount>().is_err());
+}
+
+#[test]
+fn from_cents() {
+    assert_eq!(Amount::from_cents(1).unwrap().to_string(), "0.01");
+    assert_eq!(Amount::from_cents(1250).unwrap().to_string(), "12.50");
+    // 999999999.99 euro, the largest allowed amount
+    assert_eq!(
+        Amount::from_cents(99_999_999_999).unwrap().to_string(),
+        Amount::MAX.to_string()
+    );
+    assert!(Amount::from_cents(100_000_000_000).is_err());
+    assert!(Amount::from_cents(u64::MAX).is_err());
+    assert!(Amount::from_cents(0).is_err());
+}
+
+#[test]
+fn accessors() {
+    let amount = "12,50".parse::<Amount>().unwrap();
+    assert_eq!(amount.euros(), 12);
+    assert_eq!(amount.cents(), 50);
+    assert_eq!(amount.total_cents(), 1250);
+
+    assert_eq!(Amount::new(amount.euros(), amount.cents()).unwrap(), amount);
+    assert_eq!(Amount::from_cents(amount.total_cents()).unwrap(), amount);
+    assert_eq!(Amount::MAX.total_cents(), 99_999_999_999);
+
+    assert!(Amount::new(0, 0).is_err());
+    assert!(Amount::new(1, 100).is_err());
+}
+
+#[test]
+fn from_str_only_digits() {
+    assert_eq!("12.5".parse::<Amount>().unwrap().to_string(), "12.50");
+    for input in [
+        "1x.50", "12.5x", "+12.50", "12.+5", "-1.50", "12.-5", " 12.50", "12. 5",
+    ] {
+        assert!(
+            matches!(
+                input.parse::<Amount>(),
+                Err(InvalidAmount::InvalidEuro { .. } | InvalidAmount::InvalidCent { .. })
+            ),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn from_payload_str() {
+    for (input, expected) in [("12", "12.00"), ("12.5", "12.50"), ("12,50", "12.50")] {
+        assert_eq!(
+            Amount::from_payload_str(input).unwrap().to_string(),
+            expected
+        );
+    }
+    assert!(matches!(
+        Amount::from_payload_str("12x"),
+        Err(InvalidAmount::InvalidEuro { input, .. }) if input == "12x"
+    ));
+    assert!(matches!(
+        Amount::from_payload_str("+12"),
+        Err(InvalidAmount::InvalidEuro { .. })
+    ));
+    assert!(matches!(
+        Amount::from_payload_str("0"),
+        Err(InvalidAmount::Zero)
+    ));
+    assert!(matches!(
+        Amount::from_payload_str("1000000000"),
+        Err(InvalidAmount::OutOfRange { .. })
+    ));
+
+    let code = "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000\nEUR12"
+        .parse::<EpcQr>()
+        .unwrap();
+    assert_eq!(code.amount().unwrap().to_string(), "12.00");
+}
+
+#[test]
+fn zero_amount() {
+    // a zero amount can't be constructed, codes without an amount leave the amount line empty instead
+    for input in ["0.00", "0,0", "00.00"] {
+        assert!(
+            matches!(input.parse::<Amount>(), Err(InvalidAmount::Zero)),
+            "{input}"
+        );
+    }
+    assert!(matches!(Amount::new(0, 0), Err(InvalidAmount::Zero)));
+    assert!(matches!(Amount::from_cents(0), Err(InvalidAmount::Zero)));
+    assert!(matches!(
+        "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000\nEUR0.00".parse::<EpcQr>(),
+        Err(InvalidPayload::InvalidAmount(InvalidAmount::Zero))
+    ));
+
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000").with_purpose(Some("GDDS"));
+    assert_eq!(code.to_string().lines().nth(7), Some(""));
+    let code = code.with_amount(Some(Amount::new(0, 1).unwrap()));
+    assert_eq!(code.to_string().lines().nth(7), Some("EUR0.01"));
+}
+
+#[test]
+fn with_amounts() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_remittance(Some(Remittance::Text("Donation")))
+        .with_amount(Some(Amount::new(1, 0).unwrap()));
+    let amounts = ["5.00", "10.00", "25.00"].map(|amount| amount.parse::<Amount>().unwrap());
+
+    let codes = code.with_amounts(&amounts);
+    assert_eq!(codes.len(), amounts.len());
+    for (tiered, amount) in codes.iter().zip(&amounts) {
+        assert_eq!(tiered.amount(), Some(amount));
+        assert_eq!(tiered.clone().with_amount(code.amount().cloned()), code);
+    }
+    assert!(code.with_amounts(&[]).is_empty());
+}
@@ -0,0 +1,36 @@
+#![cfg(feature = "zip")]
+
+use std::io::{Cursor, Read};
+
+use epc_qr_code_generator::batch::write_batch_zip;
+
+#[test]
+fn zip_with_manifest() {
+    let csv = "name,iban,amount\n\
+        Max Mustermann,DE89370400440532013000,12.50\n\
+        Erika Mustermann,DE00370400440532013000,\n\
+        Max Mustermann,DE89370400440532013000,\n";
+
+    let archive = write_batch_zip(csv.as_bytes(), Cursor::new(Vec::new())).unwrap();
+    let mut archive = zip::ZipArchive::new(archive).unwrap();
+
+    let mut manifest = String::new();
+    archive
+        .by_name("manifest.csv")
+        .unwrap()
+        .read_to_string(&mut manifest)
+        .unwrap();
+    let lines = manifest.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "line,file_name,status");
+    assert_eq!(lines[1], "2,epc-DE89370400440532013000-qr-code.png,ok");
+    assert!(lines[2].starts_with("3,,"));
+    assert_eq!(lines[3], "4,4-epc-DE89370400440532013000-qr-code.png,ok");
+
+    let mut image = Vec::new();
+    archive
+        .by_name("4-epc-DE89370400440532013000-qr-code.png")
+        .unwrap()
+        .read_to_end(&mut image)
+        .unwrap();
+    assert!(image.starts_with(b"\x89PNG"));
+}
@@ -0,0 +1,199 @@
+#![cfg(feature = "cli")]
+
+use clap::Parser;
+use epc_qr_code_generator::cli::{run, CliArgs};
+use epc_qr_code_generator::{GenerationError, InvalidEpcCode};
+
+fn args(extra: &[&str]) -> CliArgs {
+    let template =
+        std::env::temp_dir().join("epc-qr-code-generator-cli-{reference-}{amount}.{ext}");
+    let mut args = vec![
+        "epc-qr-code-generator",
+        "-q",
+        "--filename-template",
+        template.to_str().unwrap(),
+        "Max Mustermann",
+        "DE89370400440532013000",
+    ];
+    args.extend_from_slice(extra);
+    CliArgs::parse_from(args)
+}
+
+#[test]
+fn writes_one_file_per_amount() {
+    let paths = run(args(&["-r", "A/1", "--amounts", "5.00,10.00"])).unwrap();
+
+    assert_eq!(paths.len(), 2);
+    for path in paths {
+        let file_name = path.file_name().unwrap().to_str().unwrap();
+        assert!(file_name.starts_with("epc-qr-code-generator-cli-A_1-"));
+        assert!(path.extension().is_some_and(|ext| ext == "png"));
+        image::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn dry_run() {
+    assert!(run(args(&["--dry-run", "-a", "5.00"])).unwrap().is_empty());
+
+    assert!(matches!(
+        run(args(&["--dry-run", "--bic", "NOTABIC"])),
+        Err(GenerationError::InvalidEpcCode(
+            InvalidEpcCode::InvalidFieldLength { .. }
+        ))
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn dry_run_json() {
+    let dry_run = |extra: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_epc-qr-code-generator"))
+            .args([
+                "--dry-run",
+                "--json",
+                "Max Mustermann",
+                "DE89370400440532013000",
+            ])
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+
+    let output = dry_run(&["--amounts", "5.00,10.00"]);
+    assert!(output.status.success());
+    let reports = String::from_utf8(output.stdout).unwrap();
+    let reports = reports
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0]["valid"], true);
+    assert!(reports[0]["payload"].as_str().unwrap().ends_with("EUR5.0"));
+    assert!(reports[0]["bytes"].as_u64().unwrap() > 0);
+    assert!(reports[1]["qr_code"]["version"].as_i64().unwrap() >= 1);
+
+    let output = dry_run(&["--bic", "NOTABIC"]);
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["valid"], false);
+    assert_eq!(report["field_errors"].as_array().unwrap().len(), 1);
+    assert!(report["qr_code"].is_null());
+
+    assert!(CliArgs::try_parse_from([
+        "epc-qr-code-generator",
+        "--json",
+        "Max Mustermann",
+        "DE89370400440532013000",
+    ])
+    .is_err());
+}
+
+#[test]
+fn example() {
+    let args = CliArgs::parse_from(["epc-qr-code-generator", "-q", "--dry-run", "--example"]);
+    assert!(run(args).unwrap().is_empty());
+
+    assert!(CliArgs::try_parse_from([
+        "epc-qr-code-generator",
+        "--example",
+        "Max Mustermann",
+        "DE89370400440532013000",
+    ])
+    .is_err());
+}
+
+#[test]
+fn check() {
+    let check = |input: &str| {
+        run(CliArgs::parse_from([
+            "epc-qr-code-generator",
+            "--check",
+            input,
+        ]))
+    };
+
+    let payload = "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000\nEUR12.5";
+    assert!(check(payload).unwrap().is_empty());
+    assert!(check(&payload.replace('\n', "\r\n")).is_ok());
+    assert!(check(&payload.replace('\n', r"\n")).is_ok());
+    assert!(check(&payload.replace('\n', r"\r\n")).is_ok());
+
+    let path = std::env::temp_dir().join("epc-qr-code-generator-check.txt");
+    std::fs::write(&path, format!("{payload}\n")).unwrap();
+    let result = check(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+    assert!(result.is_ok());
+
+    // the check digits of the IBAN don't match
+    assert!(matches!(
+        check(&payload.replace("13000", "13001")),
+        Err(GenerationError::InvalidEpcCode(
+            InvalidEpcCode::InvalidFieldLength { .. }
+        ))
+    ));
+    assert!(matches!(
+        check("BCD\n003"),
+        Err(GenerationError::InvalidPayload(_))
+    ));
+    assert!(CliArgs::try_parse_from(["epc-qr-code-generator", "--check", payload, "-q"]).is_err());
+}
+
+#[test]
+#[cfg(feature = "zip")]
+fn batch_zip() {
+    let dir = std::env::temp_dir();
+    let csv = dir.join("epc-qr-code-generator-cli-batch.csv");
+    let zip = dir.join("epc-qr-code-generator-cli-batch.zip");
+    std::fs::write(
+        &csv,
+        "name,iban,amount\nMax Mustermann,DE89370400440532013000,12.50\n",
+    )
+    .unwrap();
+
+    let args = CliArgs::parse_from([
+        "epc-qr-code-generator",
+        "--batch",
+        csv.to_str().unwrap(),
+        "--zip",
+        zip.to_str().unwrap(),
+    ]);
+    assert_eq!(run(args).unwrap(), vec![zip.clone()]);
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&zip).unwrap()).unwrap();
+    assert!(archive.by_name("manifest.csv").is_ok());
+    assert!(archive
+        .by_name("epc-DE89370400440532013000-qr-code.png")
+        .is_ok());
+    std::fs::remove_file(&csv).unwrap();
+    std::fs::remove_file(&zip).unwrap();
+
+    for invalid in [
+        &["--batch", "rows.csv"][..],
+        &["--zip", "out.zip"],
+        &["--batch", "rows.csv", "--zip", "out.zip", "Max Mustermann"],
+    ] {
+        let args = std::iter::once("epc-qr-code-generator").chain(invalid.iter().copied());
+        assert!(CliArgs::try_parse_from(args).is_err(), "{invalid:?}");
+    }
+}
+
+#[test]
+#[cfg(feature = "clipboard")]
+fn clipboard_errors_are_returned() {
+    // headless environments have no clipboard, otherwise it is unlikely to hold an IBAN during the tests
+    let args = CliArgs::parse_from([
+        "epc-qr-code-generator",
+        "-q",
+        "--dry-run",
+        "--from-clipboard",
+        "Max Mustermann",
+    ]);
+    match run(args) {
+        Err(GenerationError::Clipboard(_) | GenerationError::NoIbanInClipboard) => {}
+        // the clipboard happened to contain an IBAN
+        Ok(paths) => assert!(paths.is_empty()),
+        Err(err) => panic!("unexpected error {err}"),
+    }
+}
@@ -0,0 +1,27 @@
+#![cfg(feature = "decode")]
+
+use epc_qr_code_generator::render::{RenderOptions, Rotation};
+use epc_qr_code_generator::{CharacterSet, EpcQr, ImageFormat, Remittance};
+
+#[test]
+fn verify_decodes_payload() {
+    let code = EpcQr::new("Jürgen Müller", "DE89370400440532013000")
+        .with_character_set(CharacterSet::ISO8859_01)
+        .with_remittance(Some(Remittance::Text("Rechnung 4711")));
+    let path = std::env::temp_dir().join("epc-qr-code-generator-decode.png");
+    for options in [
+        RenderOptions::default(),
+        RenderOptions::default().with_rotation(Rotation::R270),
+        RenderOptions::default()
+            .with_colors([0, 0, 128], [255, 255, 200])
+            .with_canvas_size(Some(500)),
+    ] {
+        code.generate_image_file_with_options(
+            Some(ImageFormat::png()),
+            &path,
+            &options.with_verify(true),
+        )
+        .unwrap();
+    }
+    std::fs::remove_file(&path).unwrap();
+}
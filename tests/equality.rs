@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+
+use epc_qr_code_generator::{Amount, EpcQr, EpcVersion, LineEnding, Remittance};
+
+#[test]
+fn differently_formatted_inputs_are_equal() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000").with_purpose(Some("GDDS"));
+    let formatted = EpcQr::new("Max Mustermann", "de89 3704 0044 0532 0130 00")
+        .with_purpose(Some(" gdds "))
+        .with_internal_reference(Some("INV-42"));
+    assert!(code == formatted);
+    assert_eq!(code.to_string(), formatted.to_string());
+
+    let owned = formatted.clone().into_owned();
+    assert!(owned == code);
+
+    let set = [code, formatted].into_iter().collect::<HashSet<_>>();
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn different_payments_are_not_equal() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    assert!(code != EpcQr::new("Erika Mustermann", "DE89370400440532013000"));
+    assert!(code != code.clone().with_purpose(Some("GDDS")));
+    // settings changing the payload without changing the payment
+    assert!(code != code.clone().with_line_ending(LineEnding::CrLf));
+    assert!(code != code.clone().with_emit_trailing_empty(true));
+    assert!(code != code.clone().with_empty_remittance());
+    assert!(
+        code.clone().with_normalize_whitespace(true)
+            == EpcQr::new(" Max   Mustermann", "DE89370400440532013000")
+                .with_normalize_whitespace(true)
+    );
+}
+
+#[test]
+fn payload_fingerprint() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    // FNV-1a of the payload, which must not change between versions
+    assert_eq!(code.payload_fingerprint(), 0xaae4b39e86fc8bf5);
+
+    let owned = code
+        .clone()
+        .into_owned()
+        .with_internal_reference(Some("INV-42".to_owned()));
+    assert_eq!(owned.payload_fingerprint(), code.payload_fingerprint());
+    let normalized =
+        EpcQr::new(" Max   Mustermann", "DE89370400440532013000").with_normalize_whitespace(true);
+    assert_eq!(normalized.payload_fingerprint(), code.payload_fingerprint());
+
+    let crlf = code.clone().with_line_ending(LineEnding::CrLf);
+    assert_ne!(crlf.payload_fingerprint(), code.payload_fingerprint());
+    let purpose = code.clone().with_purpose(Some("GDDS"));
+    assert_ne!(purpose.payload_fingerprint(), code.payload_fingerprint());
+}
+
+#[test]
+fn equal_codes_have_equal_payloads() {
+    let base = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_amount(Some(Amount::new(12, 50).unwrap()))
+        .with_purpose(Some("GDDS"));
+    let text = "a".repeat(141);
+    let variants = [
+        base.clone(),
+        EpcQr::new(" Max  Mustermann ", "de89 3704 0044 0532 0130 00")
+            .with_amount(Some(Amount::new(12, 50).unwrap()))
+            .with_purpose(Some("gdds ")),
+        base.clone().with_normalize_whitespace(true),
+        base.clone().with_epc_version(Some(EpcVersion::V2)),
+        base.clone().with_line_ending(LineEnding::CrLf),
+        base.clone().with_emit_trailing_empty(true),
+        base.clone().with_empty_remittance(),
+        base.clone().with_strict_compatibility(true),
+        base.clone()
+            .with_remittance(Some(Remittance::Text(text.as_str())))
+            .with_split_long_remittance(true),
+        base.clone()
+            .with_remittance(Some(Remittance::Text(text.as_str()))),
+    ];
+
+    for first in &variants {
+        for second in &variants {
+            assert_eq!(
+                first == second,
+                first.to_string() == second.to_string(),
+                "{first:?} {second:?}"
+            );
+            if first == second {
+                assert_eq!(first.payload_fingerprint(), second.payload_fingerprint());
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use epc_qr_code_generator::ImageFormat;
+
+#[test]
+fn supported_extensions() {
+    let extensions = ImageFormat::supported_extensions();
+    assert!(extensions.contains(&"png"));
+    assert!(extensions.contains(&"bmp"));
+    assert_eq!(extensions.contains(&"qoi"), cfg!(feature = "qoi"));
+}
+
+#[test]
+fn mime_types() {
+    for format in ImageFormat::SUPPORTED {
+        let parsed = ImageFormat::from_mime(format.mime_type()).unwrap();
+        assert_eq!(parsed.extension(), format.extension());
+    }
+
+    assert_eq!(ImageFormat::png().mime_type(), "image/png");
+    assert_eq!(ImageFormat::jpeg().mime_type(), "image/jpeg");
+    assert_eq!(
+        ImageFormat::from_mime("Image/JPEG; q=0.8")
+            .unwrap()
+            .extension(),
+        "jpg"
+    );
+    assert_eq!(
+        ImageFormat::from_mime("image/x-qoi").is_some(),
+        cfg!(feature = "qoi")
+    );
+    assert!(ImageFormat::from_mime("image/svg+xml").is_none());
+    assert!(ImageFormat::from_mime("image/webp").is_none());
+}
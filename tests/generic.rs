@@ -0,0 +1,33 @@
+use epc_qr_code_generator::generic::{GenericPayment, GenericPaymentError};
+use epc_qr_code_generator::EpcQr;
+
+#[test]
+fn from_generic_payment_fields() {
+    let payment = GenericPayment {
+        name: Some("Max Mustermann".to_owned()),
+        iban: Some("DE89 3704 0044 0532 0130 00".to_owned()),
+        amount: Some("12".to_owned()),
+        currency: Some("EUR".to_owned()),
+        reference: Some("RF18539007547034".to_owned()),
+        ..GenericPayment::default()
+    };
+
+    let code = EpcQr::from_generic_payment_fields(payment.clone()).unwrap();
+    assert_eq!(
+        code.to_string(),
+        "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000\nEUR12.0\n\nRF18539007547034"
+    );
+
+    let payment = GenericPayment {
+        currency: Some("CHF".to_owned()),
+        ..payment
+    };
+    assert!(matches!(
+        EpcQr::from_generic_payment_fields(payment),
+        Err(GenericPaymentError::UnsupportedCurrency(_))
+    ));
+    assert!(matches!(
+        EpcQr::from_generic_payment_fields(GenericPayment::default()),
+        Err(GenericPaymentError::MissingField("name"))
+    ));
+}
@@ -0,0 +1,4 @@
+#[test]
+fn ignored_builder_result() {
+    trybuild::TestCases::new().compile_fail("tests/ui/must_use.rs");
+}
@@ -0,0 +1,29 @@
+#![cfg(feature = "rayon")]
+
+use epc_qr_code_generator::batch::generate_batch_parallel;
+use epc_qr_code_generator::render::RenderOptions;
+use epc_qr_code_generator::{EpcQr, ImageFormat};
+
+#[test]
+fn same_as_sequential() {
+    let codes = [
+        EpcQr::new("Max Mustermann", "DE89370400440532013000"),
+        // wrong check digits
+        EpcQr::new("Max Mustermann", "DE00370400440532013000"),
+        EpcQr::new("Erika Mustermann", "AT611904300234573201"),
+    ];
+    let format = ImageFormat::png();
+    let options = RenderOptions::default();
+
+    let parallel = generate_batch_parallel(&codes, &format, &options);
+
+    assert_eq!(parallel.len(), codes.len());
+    for (code, result) in codes.iter().zip(parallel) {
+        let sequential = code.generate_image_bytes_with_options(format.clone(), &options);
+        match (sequential, result) {
+            (Ok(sequential), Ok(parallel)) => assert_eq!(sequential, parallel),
+            (Err(_), Err(_)) => {}
+            (sequential, parallel) => panic!("{sequential:?} != {parallel:?}"),
+        }
+    }
+}
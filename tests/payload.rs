@@ -0,0 +1,495 @@
+use epc_qr_code_generator::{
+    CharacterSet, EpcField, EpcQr, EpcVersion, FieldError, GenerationError, ImageFormat,
+    InvalidEpcCode, LineEnding, Remittance,
+};
+
+#[test]
+fn purpose_without_amount() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000").with_purpose(Some("GDDS"));
+
+    assert_eq!(
+        code.to_string(),
+        "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000\n\nGDDS"
+    );
+}
+
+#[test]
+fn extra_fields() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_extra_fields(vec!["B2B", "CORE"]);
+
+    assert_eq!(
+        code.to_string(),
+        "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000\n\n\n\n\n\nB2B\nCORE"
+    );
+    assert_eq!(code.payload_bytes().unwrap().len(), code.to_string().len());
+
+    let parsed = code.to_string().parse::<EpcQr>().unwrap();
+    assert_eq!(parsed.to_string(), code.to_string());
+    assert_eq!(parsed, code);
+
+    let code = code.with_extra_fields(vec!["B2B", "CO\nRE"]);
+    assert!(!code.is_valid());
+    assert_eq!(
+        code.validate_verbose(),
+        vec![FieldError::InvalidExtraField(1)]
+    );
+    assert!(code.validate().unwrap_err().field_names().is_empty());
+}
+
+#[test]
+fn ascii_is_encoded_identically_in_all_character_sets() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000").with_info(Some("Danke"));
+    let utf8 = code.payload_bytes().unwrap();
+
+    for character_set in [
+        CharacterSet::ISO8859_01,
+        CharacterSet::ISO8859_05,
+        CharacterSet::ISO8859_15,
+    ] {
+        let code = code.clone().with_character_set(character_set);
+        let bytes = code.payload_bytes().unwrap();
+        assert_eq!(bytes, code.to_string().as_bytes());
+        // only the character set line differs
+        assert_eq!(bytes.len(), utf8.len());
+    }
+}
+
+#[test]
+fn combined_remittance() {
+    let code = EpcQr::new(
+        "Max Mustermann".to_owned(),
+        "DE89370400440532013000".to_owned(),
+    )
+    .with_combined_remittance("RF18539007547034", "Invoice 42");
+    assert_eq!(
+        code.to_string(),
+        "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000\n\n\n\nRF18539007547034 Invoice 42"
+    );
+
+    // 16 characters of reference, a space and 123 characters of text are the maximum of 140
+    let text = "t".repeat(123);
+    let code = code.with_combined_remittance("RF18539007547034", &text);
+    assert!(code.validate_verbose().is_empty());
+
+    let text = "t".repeat(124);
+    let code = code.with_combined_remittance("RF18539007547034", &text);
+    assert_eq!(
+        code.validate_verbose(),
+        vec![FieldError::InvalidTextLength(141)]
+    );
+}
+
+#[test]
+fn debug_line() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000").with_info(Some(r"C:\n"));
+
+    let line = code.to_debug_line();
+    assert!(!line.contains('\n'));
+    assert_eq!(
+        line,
+        r"BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000\n\n\n\n\nC:\\n"
+    );
+}
+
+#[test]
+fn encoding_modes() {
+    use qrcode::optimize::{Optimizer, Parser};
+    use qrcode::types::Mode;
+
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000").with_bic(Some("COBADEFFXXX"));
+    let data = code.payload_bytes().unwrap();
+
+    let segments = Optimizer::new(Parser::new(&data), code.qr_version().unwrap())
+        .map(|segment| (segment.mode, &data[segment.begin..segment.end]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        segments,
+        [
+            (Mode::Byte, &b"BCD\n001\n1\nSCT\n"[..]),
+            (Mode::Alphanumeric, b"COBADEFFXXX"),
+            (Mode::Byte, b"\nMax Mustermann\nDE"),
+            (Mode::Numeric, b"89370400440532013000"),
+        ]
+    );
+}
+
+#[test]
+fn trailing_empty_fields() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    assert_eq!(
+        code.to_string(),
+        "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000"
+    );
+
+    let code = code.with_emit_trailing_empty(true);
+    assert_eq!(
+        code.to_string(),
+        "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000\n\n\n\n\n"
+    );
+    assert_eq!(code.payload_bytes().unwrap(), code.to_string().as_bytes());
+    assert_eq!(code.to_string().parse::<EpcQr>().unwrap(), code);
+}
+
+#[test]
+fn explicit_version_2_keeps_bic() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_bic(Some("COBADEFFXXX"))
+        .with_epc_version(Some(EpcVersion::V2));
+
+    assert_eq!(code.epc_version(), EpcVersion::V2);
+    assert!(code.is_valid());
+    assert_eq!(
+        code.to_string(),
+        "BCD\n002\n1\nSCT\nCOBADEFFXXX\nMax Mustermann\nDE89370400440532013000"
+    );
+}
+
+#[test]
+fn split_long_remittance() {
+    let text = "t".repeat(200);
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_remittance(Some(Remittance::Text(text.as_str())));
+    assert_eq!(
+        code.validate_verbose(),
+        vec![FieldError::InvalidTextLength(200)]
+    );
+
+    let code = code.with_split_long_remittance(true);
+    assert!(code.validate_verbose().is_empty());
+    let fields = code.as_fields();
+    assert_eq!(fields[10], ("text", Some("t".repeat(140))));
+    assert_eq!(fields[11], ("info", Some(format!("...{}", "t".repeat(60)))));
+    assert!(code.payload_bytes().is_ok());
+
+    // 110 characters of overflow don't fit into the info either
+    let text = "t".repeat(250);
+    let code = code.with_remittance(Some(Remittance::Text(text.as_str())));
+    assert_eq!(
+        code.validate_verbose(),
+        vec![FieldError::InvalidInfoLength(113)]
+    );
+}
+
+#[test]
+fn field_line_index() {
+    fn line(payload: &str, index: Option<usize>) -> Option<&str> {
+        index.map(|index| payload.lines().nth(index).unwrap())
+    }
+
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000").with_purpose(Some("GDDS"));
+    let payload = code.to_string();
+    assert_eq!(code.field_line_index(EpcField::ServiceTag), Some(0));
+    assert_eq!(
+        line(&payload, code.field_line_index(EpcField::BeneficiaryName)),
+        Some("Max Mustermann")
+    );
+    assert_eq!(
+        line(&payload, code.field_line_index(EpcField::Purpose)),
+        Some("GDDS")
+    );
+    // unset fields before the purpose keep their empty line, the trailing ones are omitted
+    assert_eq!(
+        line(&payload, code.field_line_index(EpcField::Amount)),
+        Some("")
+    );
+    assert_eq!(code.field_line_index(EpcField::Reference), None);
+    assert_eq!(code.field_line_index(EpcField::Info), None);
+
+    let code = code.with_remittance(Some(Remittance::Text("Invoice 42")));
+    let payload = code.to_string();
+    assert_eq!(
+        line(&payload, code.field_line_index(EpcField::Text)),
+        Some("Invoice 42")
+    );
+    assert_eq!(code.field_line_index(EpcField::Info), None);
+
+    let code = code.with_extra_fields(vec!["B2B", "CORE"]);
+    let payload = code.to_string();
+    assert_eq!(
+        line(&payload, code.field_line_index(EpcField::Info)),
+        Some("")
+    );
+    assert_eq!(
+        line(&payload, code.field_line_index(EpcField::Extra(1))),
+        Some("CORE")
+    );
+    assert_eq!(code.field_line_index(EpcField::Extra(2)), None);
+
+    let code =
+        EpcQr::new("Max Mustermann", "DE89370400440532013000").with_emit_trailing_empty(true);
+    assert_eq!(code.field_line_index(EpcField::Info), Some(11));
+    assert_eq!(code.field_line_index(EpcField::Extra(0)), None);
+}
+
+#[test]
+fn internal_reference_is_not_encoded() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_info(Some("Danke"))
+        .with_internal_reference(Some("INV-2023-0042"));
+
+    assert_eq!(code.internal_reference(), Some("INV-2023-0042"));
+    assert!(!code.to_string().contains("INV-2023-0042"));
+    assert!(code
+        .as_fields()
+        .iter()
+        .all(|(_, value)| value.as_deref() != Some("INV-2023-0042")));
+    assert_eq!(code, code.clone().with_internal_reference(None));
+    assert_eq!(
+        code.clone().into_owned().internal_reference(),
+        Some("INV-2023-0042")
+    );
+
+    let file_name = code
+        .file_name_from_template("{internal_reference-}{account}.{ext}", &ImageFormat::png())
+        .unwrap();
+    assert_eq!(file_name, "INV-2023-0042-DE89370400440532013000.png");
+}
+
+#[test]
+fn empty_remittance() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000").with_purpose(Some("GDDS"));
+    // an absent remittance is trimmed with the other trailing unset fields
+    assert_eq!(
+        code.to_string(),
+        "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000\n\nGDDS"
+    );
+
+    // a present but empty remittance keeps its reference and text lines
+    let empty = code.clone().with_empty_remittance();
+    assert_eq!(
+        empty.to_string(),
+        "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000\n\nGDDS\n\n"
+    );
+    assert_eq!(empty.field_line_index(EpcField::Text), Some(10));
+    assert_eq!(empty.field_line_index(EpcField::Info), None);
+    assert!(empty.is_valid());
+    assert_ne!(empty, code);
+    assert_eq!(empty.to_string().parse::<EpcQr>().unwrap(), empty);
+
+    // setting the remittance again replaces the empty one
+    assert_eq!(empty.with_remittance(None).to_string(), code.to_string());
+}
+
+#[test]
+fn crlf_size() {
+    let short =
+        EpcQr::new("Max Mustermann", "DE89370400440532013000").with_line_ending(LineEnding::CrLf);
+    assert_eq!(
+        short.payload_bytes().unwrap(),
+        b"BCD\r\n002\r\n1\r\nSCT\r\n\r\nMax Mustermann\r\nDE89370400440532013000"
+    );
+
+    let name = "ä".repeat(70);
+    let text = "ä".repeat(70);
+    let code = EpcQr::new(name.as_str(), "DE89370400440532013000")
+        .with_remittance(Some(Remittance::Text(text.as_str())));
+    let lf = code.payload_bytes().unwrap();
+    assert_eq!(lf.len(), 322);
+
+    // every one of the 10 line breaks takes an extra byte
+    let code = code.with_line_ending(LineEnding::CrLf);
+    assert_eq!(code.to_string().len(), lf.len() + 10);
+    assert!(code.validate_fields().is_ok());
+    assert!(matches!(
+        code.validate(),
+        Err(InvalidEpcCode::CombinedTooLarge {
+            total: 332,
+            max: 331
+        })
+    ));
+}
+
+#[test]
+fn character_set_line() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    for (character_set, line) in [
+        (CharacterSet::Utf8, "1"),
+        (CharacterSet::ISO8859_01, "2"),
+        (CharacterSet::ISO8859_02, "3"),
+        (CharacterSet::ISO8859_04, "4"),
+        (CharacterSet::ISO8859_05, "5"),
+        (CharacterSet::ISO8859_07, "6"),
+        (CharacterSet::ISO8859_10, "7"),
+        (CharacterSet::ISO8859_15, "8"),
+    ] {
+        let payload = code.clone().with_character_set(character_set).to_string();
+        assert_eq!(payload.lines().nth(2), Some(line));
+    }
+}
+
+#[test]
+fn character_set_names() {
+    for (character_set, alias) in [
+        (CharacterSet::Utf8, "utf8"),
+        (CharacterSet::ISO8859_01, "latin1"),
+        (CharacterSet::ISO8859_02, "latin2"),
+        (CharacterSet::ISO8859_04, "latin4"),
+        (CharacterSet::ISO8859_05, "cyrillic"),
+        (CharacterSet::ISO8859_07, "greek"),
+        (CharacterSet::ISO8859_10, "latin6"),
+        (CharacterSet::ISO8859_15, "latin9"),
+    ] {
+        let name = character_set.to_string();
+        assert_eq!(name.parse::<CharacterSet>().unwrap(), character_set);
+        assert_eq!(
+            name.to_uppercase().parse::<CharacterSet>().unwrap(),
+            character_set
+        );
+        assert_eq!(alias.parse::<CharacterSet>().unwrap(), character_set);
+    }
+    assert_eq!(
+        " ISO8859-15 ".parse::<CharacterSet>().unwrap(),
+        CharacterSet::ISO8859_15
+    );
+    assert!("iso-8859-3".parse::<CharacterSet>().is_err());
+    assert!("".parse::<CharacterSet>().is_err());
+}
+
+#[test]
+fn single_byte_encoding() {
+    let code = EpcQr::new("Jürgen Mäder", "DE89370400440532013000")
+        .with_character_set(CharacterSet::ISO8859_01);
+    let bytes = code.payload_bytes().unwrap();
+    let name = bytes.split(|&byte| byte == b'\n').nth(5).unwrap();
+    assert_eq!(name, b"J\xFCrgen M\xE4der");
+    assert_eq!(bytes.len(), code.to_string().chars().count());
+
+    let euro = code.clone().with_info(Some("5 €"));
+    assert!(matches!(
+        euro.payload_bytes(),
+        Err(InvalidEpcCode::UnencodableCharacter {
+            character: '€', ..
+        })
+    ));
+    let bytes = euro
+        .with_character_set(CharacterSet::ISO8859_15)
+        .payload_bytes()
+        .unwrap();
+    assert!(bytes.ends_with(b"\n5 \xA4"));
+}
+
+#[test]
+fn whitespace_normalization() {
+    let code =
+        EpcQr::new("\tJohn  \t Doe ", "DE89370400440532013000").with_info(Some(" Danke\tschön\n"));
+    assert!(code.to_string().contains("\n\tJohn  \t Doe \n"));
+
+    let code = code.with_normalize_whitespace(true);
+    assert_eq!(
+        code.to_string(),
+        "BCD\n002\n1\nSCT\n\nJohn Doe\nDE89370400440532013000\n\n\n\n\nDanke schön"
+    );
+    assert!(code.is_valid());
+
+    // the length is checked after trimming
+    let padded = format!("  {}\t", "M".repeat(70));
+    let code = EpcQr::new(padded.as_str(), "DE89370400440532013000");
+    assert_eq!(
+        code.validate_verbose(),
+        vec![FieldError::InvalidNameLength(73)]
+    );
+    assert!(code.with_normalize_whitespace(true).is_valid());
+
+    let blank = EpcQr::new(" \t ", "DE89370400440532013000").with_normalize_whitespace(true);
+    assert_eq!(
+        blank.validate_verbose(),
+        vec![FieldError::InvalidNameLength(0)]
+    );
+}
+
+#[test]
+fn as_fields_match_payload() {
+    let join = |code: &EpcQr<&str>| {
+        code.as_fields()
+            .into_iter()
+            .map(|(_, value)| value.unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_bic(Some("COBADEFFXXX"))
+        .with_amount(Some("12.50".parse().unwrap()))
+        .with_purpose(Some("GDDS"))
+        .with_remittance(Some(Remittance::Text("Invoice 4711")))
+        .with_info(Some("Thanks"));
+    assert_eq!(code.as_fields().len(), 12);
+    assert_eq!(join(&code), code.to_string());
+
+    let minimal = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let labels = minimal
+        .as_fields()
+        .into_iter()
+        .map(|(label, _)| label)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        [
+            "service",
+            "version",
+            "charset",
+            "identification",
+            "bic",
+            "name",
+            "iban",
+            "amount",
+            "purpose",
+            "reference",
+            "text",
+            "info"
+        ]
+    );
+    // without the trailing empty lines the payload is a prefix of the joined fields
+    assert!(join(&minimal).starts_with(&minimal.to_string()));
+    assert_eq!(
+        join(&minimal),
+        minimal.clone().with_emit_trailing_empty(true).to_string()
+    );
+    let extra = minimal.with_extra_fields(vec!["B2B"]);
+    assert_eq!(join(&extra), extra.to_string());
+}
+
+#[test]
+fn file_name_template() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let with_fields = code
+        .clone()
+        .with_bic(Some("COBADEFFXXX"))
+        .with_amount(Some("12.5".parse().unwrap()))
+        .with_remittance(Some(Remittance::Text("Invoice 4/2")));
+    let png = ImageFormat::png();
+
+    assert_eq!(
+        code.file_name(&png),
+        "epc-DE89370400440532013000-qr-code.png"
+    );
+    assert_eq!(
+        with_fields.file_name(&png),
+        "epc-COBADEFFXXX-DE89370400440532013000-Invoice_4_2-qr-code.png"
+    );
+    // affixes are only included with a value
+    let template = "{amount}{-bic}.{ext}";
+    assert_eq!(
+        with_fields
+            .file_name_from_template(template, &ImageFormat::gif())
+            .unwrap(),
+        "12.50-COBADEFFXXX.gif"
+    );
+    assert_eq!(
+        code.clone()
+            .with_amount(Some("12.5".parse().unwrap()))
+            .file_name_from_template(template, &png)
+            .unwrap(),
+        "12.50.png"
+    );
+
+    assert!(matches!(
+        code.file_name_from_template("{account", &png),
+        Err(GenerationError::InvalidTemplate(template)) if template == "{account"
+    ));
+    assert!(matches!(
+        code.file_name_from_template("{iban}.{ext}", &png),
+        Err(GenerationError::UnknownPlaceholder(name)) if name == "iban"
+    ));
+}
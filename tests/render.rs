@@ -0,0 +1,639 @@
+use epc_qr_code_generator::render::{self, ChromaSubsampling, RenderOptions, Rotation};
+use epc_qr_code_generator::{EpcQr, GenerationError, ImageFormat, Remittance};
+use qrcode::{EcLevel, Version};
+
+#[test]
+fn configured_luma() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let options = RenderOptions::default().with_luma(30, 240);
+
+    let mut buffer = image::GrayImage::new(0, 0);
+    code.render_into(&mut buffer, &options).unwrap();
+
+    // the top left corner is part of the quiet zone, the finder pattern starts after 4 modules of 8 pixels
+    assert_eq!(buffer.get_pixel(0, 0).0, [240]);
+    assert_eq!(buffer.get_pixel(32, 32).0, [30]);
+    assert!(buffer.pixels().all(|px| px.0 == [30] || px.0 == [240]));
+}
+
+#[test]
+fn ascii_art() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let modules = code
+        .qr_code_info(&RenderOptions::default())
+        .unwrap()
+        .modules;
+
+    let art = code.to_ascii_art().unwrap();
+
+    let lines = art.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), modules + 8);
+    assert!(lines.iter().all(|line| line.len() == 2 * (modules + 8)));
+    assert!(art.chars().all(|c| matches!(c, '#' | ' ' | '\n')));
+}
+
+#[test]
+fn auto_downgrade_ecc() {
+    // 95 bytes, more than the 84 bytes a version 5 code holds at level M, but less than the 106 at level L
+    let text = "t".repeat(39);
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_remittance(Some(Remittance::Text(text.as_str())));
+    assert_eq!(code.payload_bytes().unwrap().len(), 95);
+
+    let options = RenderOptions::default().with_version(Some(Version::Normal(5)));
+    assert!(matches!(
+        code.qr_code_info(&options),
+        Err(GenerationError::PayloadTooLargeForVersion { .. })
+    ));
+
+    let options = options.with_auto_downgrade_ecc(true);
+    assert_eq!(code.qr_code_info(&options).unwrap().ec_level, EcLevel::L);
+}
+
+#[test]
+fn reproducible_png() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let options = RenderOptions::default().with_reproducible(true);
+
+    let first = code
+        .generate_image_bytes_with_options(ImageFormat::png(), &options)
+        .unwrap();
+    let second = code
+        .generate_image_bytes_with_options(ImageFormat::png(), &options)
+        .unwrap();
+    assert_eq!(first, second);
+    // the pinned settings match the current defaults
+    assert_eq!(
+        first,
+        code.generate_image_bytes(ImageFormat::png()).unwrap()
+    );
+}
+
+#[test]
+fn companion() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let options = RenderOptions::default();
+
+    let (epc, companion) = code
+        .generate_with_companion("https://pay.example.com/invoice/42?amount=12.50", &options)
+        .unwrap();
+    assert_eq!(epc, code.to_rgba_image(&options).unwrap());
+    assert_ne!(epc, companion);
+
+    for url in [
+        "pay.example.com",
+        "https://",
+        "https://pay.example.com/in voice",
+    ] {
+        assert!(matches!(
+            code.generate_with_companion(url, &options),
+            Err(GenerationError::InvalidUrl(_))
+        ));
+    }
+}
+
+/// The horizontal and vertical sampling factors of the luma and both chroma components of a JPEG image
+fn jpeg_sampling_factors(jpeg: &[u8]) -> Vec<u8> {
+    // baseline start of frame: marker, length, precision, height, width, component count, components
+    let sof = jpeg
+        .windows(2)
+        .position(|marker| marker == [0xFF, 0xC0])
+        .unwrap();
+    assert_eq!(jpeg[sof + 9], 3);
+    jpeg[sof + 10..]
+        .chunks(3)
+        .take(3)
+        .map(|component| component[1])
+        .collect()
+}
+
+#[test]
+fn jpeg_chroma_subsampling() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let options = RenderOptions::default().with_colors([0, 0, 128], [255, 255, 224]);
+    let jpeg = |options: &RenderOptions| {
+        code.generate_image_bytes_with_options(ImageFormat::jpeg(), options)
+            .unwrap()
+    };
+
+    // the sampling factors of the chroma components are relative to the largest one of the luma component
+    for (subsampling, factors) in [
+        (ChromaSubsampling::None, [0x11, 0x11, 0x11]),
+        (ChromaSubsampling::Horizontal, [0x21, 0x11, 0x11]),
+        (ChromaSubsampling::Both, [0x22, 0x11, 0x11]),
+    ] {
+        let options = options.clone().with_chroma_subsampling(subsampling);
+        assert_eq!(jpeg_sampling_factors(&jpeg(&options)), factors);
+    }
+    assert_eq!(jpeg_sampling_factors(&jpeg(&options)), [0x11, 0x11, 0x11]);
+
+    let low_quality = jpeg(&options.clone().with_jpeg_quality(10));
+    assert!(low_quality.len() < jpeg(&options).len());
+    image::load_from_memory_with_format(&low_quality, image::ImageFormat::Jpeg).unwrap();
+}
+
+#[test]
+fn gif() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let gif = code.generate_image_bytes(ImageFormat::gif()).unwrap();
+
+    let decoded = image::load_from_memory_with_format(&gif, image::ImageFormat::Gif).unwrap();
+    assert_eq!(
+        decoded.to_rgba8(),
+        code.to_rgba_image(&RenderOptions::default()).unwrap()
+    );
+}
+
+#[test]
+fn post_process_resize() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let path = std::env::temp_dir().join("epc-qr-code-generator-post-process.png");
+
+    code.generate_image_file_with(Some(ImageFormat::png()), &path, |image| {
+        *image = image.resize_exact(100, 50, image::imageops::FilterType::Nearest);
+    })
+    .unwrap();
+
+    let saved = image::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!((saved.width(), saved.height()), (100, 50));
+}
+
+#[test]
+fn module_size_for_print() {
+    // 30 cm need a Version 1 code of 21 modules with modules of about 1.43 mm, which are 16.9 pixels at 300 dpi
+    assert_eq!(render::module_size_for_print(30.0, 300, 21), 17);
+    // a Version 10 code of 57 modules has modules of about 0.53 mm, which are 6.2 pixels
+    assert_eq!(render::module_size_for_print(30.0, 300, 57), 7);
+    assert_eq!(render::module_size_for_print(1.0, 72, 21), 1);
+}
+
+#[test]
+fn recommended_min_size() {
+    let v3 = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let text = "Rechnung 4711 ".repeat(10);
+    let v10 = v3
+        .clone()
+        .with_remittance(Some(Remittance::Text(text.as_str())));
+    let info = |code: &EpcQr<&str>| code.qr_code_info(&RenderOptions::default()).unwrap();
+    assert_eq!(info(&v3).modules, 29);
+    assert_eq!(info(&v10).modules, 57);
+
+    // the code itself is a tenth of the scan distance, the quiet zone of 4 modules on each side comes on top
+    assert!((render::min_module_size_mm(30.0, 29) - 30.0 / 29.0).abs() < 1e-4);
+    assert!(render::min_module_size_mm(30.0, 57) < render::min_module_size_mm(30.0, 29));
+    let size = v3.recommended_min_size_mm(30.0).unwrap();
+    assert!((size - 30.0 / 29.0 * 37.0).abs() < 1e-3, "{size}");
+    let size = v10.recommended_min_size_mm(30.0).unwrap();
+    assert!((size - 30.0 / 57.0 * 65.0).abs() < 1e-3, "{size}");
+}
+
+#[test]
+fn version_matrix() {
+    let text = "t".repeat(140);
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_remittance(Some(Remittance::Text(text.as_str())));
+
+    let versions = code.version_matrix().unwrap();
+    let numbers = versions.map(|version| match version {
+        Version::Normal(version) | Version::Micro(version) => version,
+    });
+    assert!(numbers.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(numbers[0] < numbers[3]);
+    assert_eq!(versions[1], code.qr_version().unwrap());
+}
+
+#[test]
+fn send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<EpcQr>();
+    assert_send_sync::<epc_qr_code_generator::EpcQrRef<'static>>();
+    assert_send_sync::<RenderOptions>();
+}
+
+#[test]
+fn frame() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let plain = code.to_rgba_image(&RenderOptions::default()).unwrap();
+
+    for options in [
+        RenderOptions::default(),
+        RenderOptions::default().with_module_shape(render::ModuleShape::Dot),
+    ] {
+        let options = options.with_frame(4, [255, 0, 0]);
+        let framed = code.to_rgba_image(&options).unwrap();
+
+        assert_eq!(framed.width(), plain.width() + 8);
+        assert_eq!(framed.height(), plain.height() + 8);
+        assert_eq!(framed.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(framed.get_pixel(3, framed.height() - 1).0, [255, 0, 0, 255]);
+        // the whole quiet zone of 4 modules is kept inside the frame
+        assert_eq!(framed.get_pixel(4, 4).0, [255, 255, 255, 255]);
+        assert_eq!(framed.get_pixel(4 + 31, 4 + 31).0, [255, 255, 255, 255]);
+        assert_eq!(framed.get_pixel(4 + 32, 4 + 32).0, [0, 0, 0, 255]);
+        assert_eq!(code.qr_code_info(&options).unwrap().width, framed.width());
+    }
+
+    let path = std::env::temp_dir().join("epc-qr-code-generator-frame.png");
+    let options = RenderOptions::default()
+        .with_frame(3, [0, 0, 128])
+        .with_verify(true);
+    code.generate_image_file_with_options(Some(ImageFormat::png()), &path, &options)
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(render::parse_frame("4:000000").unwrap(), (4, [0, 0, 0]));
+    assert!(matches!(
+        render::parse_frame("4"),
+        Err(GenerationError::InvalidFrame { .. })
+    ));
+}
+
+#[test]
+fn nearest_resize_preserves_modules() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let mut unscaled = image::GrayImage::new(0, 0);
+    code.render_into(&mut unscaled, &RenderOptions::default())
+        .unwrap();
+
+    // 8 pixel modules scaled by a non-integer factor
+    let target_width = unscaled.width() + 50;
+    let options = RenderOptions::default().with_target_width(Some(target_width));
+    let mut scaled = image::GrayImage::new(0, 0);
+    code.render_into(&mut scaled, &options).unwrap();
+    let info = code.qr_code_info(&options).unwrap();
+    assert_eq!(scaled.dimensions(), (info.width, info.height));
+    assert_eq!(scaled.width(), target_width);
+
+    assert!(scaled.pixels().all(|px| px.0 == [0] || px.0 == [255]));
+    let scale = target_width as f32 / unscaled.width() as f32;
+    for y in (4..unscaled.height()).step_by(8) {
+        for x in (4..unscaled.width()).step_by(8) {
+            let scaled_px = scaled.get_pixel((x as f32 * scale) as u32, (y as f32 * scale) as u32);
+            assert_eq!(scaled_px, unscaled.get_pixel(x, y));
+        }
+    }
+
+    let smooth = options.with_resize_filter(image::imageops::FilterType::Triangle);
+    code.render_into(&mut scaled, &smooth).unwrap();
+    assert!(scaled.pixels().any(|px| px.0 != [0] && px.0 != [255]));
+
+    let path = std::env::temp_dir().join("epc-qr-code-generator-target-width.png");
+    code.generate_image_file_with_options(
+        Some(ImageFormat::png()),
+        &path,
+        &RenderOptions::default()
+            .with_target_width(Some(target_width))
+            .with_verify(true),
+    )
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn canvas() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let plain = code.to_rgba_image(&RenderOptions::default()).unwrap();
+    let options = RenderOptions::default()
+        .with_canvas_size(Some(400))
+        .with_quiet_zone_color(Some([200, 200, 255]));
+
+    let image = code.to_rgba_image(&options).unwrap();
+    assert_eq!(image.dimensions(), (400, 400));
+    let info = code.qr_code_info(&options).unwrap();
+    assert_eq!((info.width, info.height), (400, 400));
+
+    let offset = (400 - plain.width()) / 2;
+    assert_eq!(image.get_pixel(0, 0).0, [200, 200, 255, 255]);
+    // the finder pattern starts after the quiet zone of 4 modules of 8 pixels
+    assert_eq!(
+        image.get_pixel(offset + 31, offset + 31).0,
+        [200, 200, 255, 255]
+    );
+    assert_eq!(image.get_pixel(offset + 32, offset + 32).0, [0, 0, 0, 255]);
+    let end = offset + plain.width() - 1;
+    // centered: the top right finder pattern ends as far from the right as the top left one starts from the left
+    assert_eq!(image.get_pixel(end - 32, offset + 32).0, [0, 0, 0, 255]);
+    assert_eq!(
+        image.get_pixel(end - 31, offset + 32).0,
+        [200, 200, 255, 255]
+    );
+
+    let path = std::env::temp_dir().join("epc-qr-code-generator-canvas.png");
+    code.generate_image_file_with_options(
+        Some(ImageFormat::png()),
+        &path,
+        &options.clone().with_verify(true),
+    )
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(
+        code.to_rgba_image(&options.with_canvas_size(Some(100))),
+        Err(GenerationError::CanvasTooSmall {
+            canvas_size: 100,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn placeholder() {
+    let options = RenderOptions::default();
+    for size in [300, 37] {
+        let placeholder = EpcQr::placeholder_image(size, &options).into_rgba8();
+        assert_eq!(placeholder.dimensions(), (size, size));
+    }
+    // too small for 1 pixel per module of the 29 modules and the quiet zone of 4 modules on each side
+    for size in [0, 20] {
+        let placeholder = EpcQr::placeholder_image(size, &options).into_rgba8();
+        assert_eq!(placeholder.dimensions(), (37, 37));
+    }
+    let framed = options
+        .clone()
+        .with_quiet_zone_sides(4, 4, 10, 4)
+        .with_frame(3, [0, 0, 0]);
+    let placeholder = EpcQr::placeholder_image(0, &framed).into_rgba8();
+    assert_eq!(placeholder.dimensions(), (49, 49));
+
+    let placeholder = EpcQr::placeholder_image(300, &options).into_rgba8();
+    // 37 modules of 8 pixels centered with a margin of 2 pixels, the finder pattern starts after the quiet zone
+    assert_eq!(
+        placeholder.get_pixel(2 + 31, 2 + 31).0,
+        [255, 255, 255, 255]
+    );
+    assert_eq!(
+        placeholder.get_pixel(2 + 32, 2 + 32).0,
+        [192, 192, 192, 255]
+    );
+    assert!(placeholder
+        .pixels()
+        .all(|px| px.0 == [255, 255, 255, 255] || px.0 == [192, 192, 192, 255]));
+}
+
+#[test]
+fn ecc_comparison() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let dir = std::env::temp_dir().join("epc-qr-code-generator-ecc-comparison");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let paths = code
+        .generate_ecc_comparison(&dir, &RenderOptions::default())
+        .unwrap();
+    let versions = code.version_matrix().unwrap();
+    assert_eq!(paths.len(), 4);
+    for ((path, level), version) in paths.iter().zip(["L", "M", "Q", "H"]).zip(versions) {
+        let Version::Normal(version) = version else {
+            unreachable!("EPC QR-Codes are never micro QR-Codes")
+        };
+        let file_name = path.file_name().unwrap().to_str().unwrap();
+        assert_eq!(file_name, format!("code-ecc{level}-v{version}.png"));
+        image::open(path).unwrap();
+    }
+
+    // the payload only fits into version 3 at levels L and M
+    let paths = code
+        .generate_ecc_comparison(&dir, &RenderOptions::default().with_max_version(Some(3)))
+        .unwrap();
+    assert_eq!(
+        paths,
+        [dir.join("code-eccL-v3.png"), dir.join("code-eccM-v3.png")]
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rotation() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    // a wider quiet zone at the bottom makes the image taller than wide
+    let options = RenderOptions::default().with_quiet_zone_sides(4, 4, 10, 4);
+    let upright = code.to_rgba_image(&options).unwrap();
+    let (width, height) = upright.dimensions();
+    assert!(width < height);
+
+    for rotation in [Rotation::R90, Rotation::R180, Rotation::R270] {
+        let options = options.clone().with_rotation(rotation);
+        let rotated = code.to_rgba_image(&options).unwrap();
+        let info = code.qr_code_info(&options).unwrap();
+        assert_eq!(rotated.dimensions(), (info.width, info.height));
+
+        let expected_dimensions = match rotation {
+            Rotation::R180 => (width, height),
+            _ => (height, width),
+        };
+        assert_eq!(rotated.dimensions(), expected_dimensions);
+        for (x, y, px) in rotated.enumerate_pixels() {
+            let (ux, uy) = match rotation {
+                Rotation::R90 => (y, height - 1 - x),
+                Rotation::R180 => (width - 1 - x, height - 1 - y),
+                _ => (width - 1 - y, x),
+            };
+            assert_eq!(px, upright.get_pixel(ux, uy));
+        }
+
+        let mut gray = image::GrayImage::new(0, 0);
+        code.render_into(&mut gray, &options).unwrap();
+        assert_eq!(gray, image::DynamicImage::ImageRgba8(rotated).into_luma8());
+    }
+
+    let path = std::env::temp_dir().join("epc-qr-code-generator-rotation.png");
+    code.generate_image_file_with_options(
+        Some(ImageFormat::png()),
+        &path,
+        &options.with_rotation(Rotation::R90).with_verify(true),
+    )
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn bmp() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let options = RenderOptions::default().with_colors([0, 0, 128], [255, 255, 224]);
+    let bmp = code
+        .generate_image_bytes_with_options(ImageFormat::bmp(), &options)
+        .unwrap();
+    assert!(bmp.starts_with(b"BM"));
+
+    let decoded = image::load_from_memory_with_format(&bmp, image::ImageFormat::Bmp).unwrap();
+    assert_eq!(decoded.to_rgba8(), code.to_rgba_image(&options).unwrap());
+
+    // the format is guessed from the extension and black and white codes are stored in grayscale
+    let path = std::env::temp_dir().join("epc-qr-code-generator.bmp");
+    code.generate_image_file(None, &path).unwrap();
+    let saved = image::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        saved.to_rgba8(),
+        code.to_rgba_image(&RenderOptions::default()).unwrap()
+    );
+}
+
+#[test]
+fn verification_failure() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    // scaled to less than a pixel per module, many modules are lost
+    let path = std::env::temp_dir().join("epc-qr-code-generator-verification-failure.png");
+    let result = code.generate_image_file_with_options(
+        Some(ImageFormat::png()),
+        &path,
+        &RenderOptions::default()
+            .with_module_size(1)
+            .with_target_width(Some(20))
+            .with_verify(true),
+    );
+    std::fs::remove_file(&path).unwrap();
+    assert!(
+        matches!(
+            result,
+            Err(GenerationError::VerificationFailed { mismatched_modules }) if 0 < mismatched_modules
+        ),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn max_version() {
+    let text = "Rechnung 4711 ".repeat(10);
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_remittance(Some(Remittance::Text(text.as_str())));
+    let options = RenderOptions::default().with_max_version(Some(5));
+
+    assert!(matches!(
+        code.to_rgba_image(&options),
+        Err(GenerationError::VersionTooHigh { got, max: 5 }) if 5 < got
+    ));
+    let short = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    assert!(short.to_rgba_image(&options).is_ok());
+}
+
+#[test]
+fn fits_at_capacity() {
+    let with_info = |length| {
+        EpcQr::new(
+            "Max Mustermann".to_owned(),
+            "DE89370400440532013000".to_owned(),
+        )
+        .with_info(Some("x".repeat(length)))
+    };
+    // 92 bytes are the most a version 5 code with medium error correction holds for this payload
+    let full = with_info(35);
+    assert_eq!(full.to_string().len(), 92);
+    assert!(full.fits(Version::Normal(5), EcLevel::M));
+
+    let over = with_info(36);
+    assert!(!over.fits(Version::Normal(5), EcLevel::M));
+    assert!(over.fits(Version::Normal(5), EcLevel::L));
+    assert!(over.fits(Version::Normal(6), EcLevel::M));
+
+    assert!(!EpcQr::new("", "DE89370400440532013000").fits(Version::Normal(40), EcLevel::L));
+}
+
+#[test]
+fn fixed_version_too_small() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000").with_remittance(Some(
+        Remittance::Text("Invoice 4711, thanks for your order"),
+    ));
+    let options = RenderOptions::default().with_version(Some(Version::Normal(2)));
+    assert!(matches!(
+        code.to_rgba_image(&options),
+        Err(GenerationError::PayloadTooLargeForVersion {
+            version: Version::Normal(2)
+        })
+    ));
+
+    let options = options.with_version(Some(Version::Normal(10)));
+    let info = code.qr_code_info(&options).unwrap();
+    assert_eq!(info.version, Version::Normal(10));
+    assert_eq!(info.modules, 57);
+}
+
+#[test]
+fn parse_color() {
+    assert_eq!(render::parse_color("#fff").unwrap(), [255, 255, 255]);
+    assert_eq!(render::parse_color("#1a2").unwrap(), [0x11, 0xaa, 0x22]);
+    assert_eq!(render::parse_color("ffffff").unwrap(), [255, 255, 255]);
+    assert_eq!(render::parse_color("#00008B").unwrap(), [0, 0, 139]);
+    for input in ["#GG0000", "abc", "#ffff", "", "#", "fffffff", "+f0000"] {
+        assert!(
+            matches!(
+                render::parse_color(input),
+                Err(GenerationError::InvalidColor { input: ref got }) if got == input
+            ),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn sheet_captions() {
+    let codes = [
+        EpcQr::new("Max Mustermann", "DE89370400440532013000")
+            .with_amount(Some("12.50".parse().unwrap())),
+        EpcQr::new("Jürgen", "DE89370400440532013000"),
+    ];
+    let options = RenderOptions::default().with_quiet_zone_color(Some([200, 200, 255]));
+    let single = codes[0].to_rgba_image(&options).unwrap();
+    let sheet = render::generate_sheet(&codes, 2, &options)
+        .unwrap()
+        .into_rgba8();
+
+    // two caption lines of 7 pixel high glyphs with 2 pixels spacing, scaled by 2 for 8 pixel modules
+    let caption_top = single.height();
+    assert_eq!(sheet.width(), 2 * single.width());
+    assert_eq!(sheet.height(), caption_top + 2 * 18);
+    assert_eq!(sheet.get_pixel(0, 0).0, [200, 200, 255, 255]);
+    assert_eq!(
+        sheet.get_pixel(0, sheet.height() - 1).0,
+        [200, 200, 255, 255]
+    );
+
+    let dark_in = |x_range: std::ops::Range<u32>, y_range: std::ops::Range<u32>| {
+        x_range
+            .flat_map(|x| y_range.clone().map(move |y| (x, y)))
+            .filter(|&(x, y)| sheet.get_pixel(x, y).0 == [0, 0, 0, 255])
+            .count()
+    };
+    let (left, right) = (0..single.width(), single.width()..sheet.width());
+    let (name_line, amount_line) = (
+        caption_top..caption_top + 18,
+        caption_top + 18..sheet.height(),
+    );
+    assert!(0 < dark_in(left.clone(), name_line.clone()));
+    assert!(0 < dark_in(left, amount_line.clone()));
+    assert!(0 < dark_in(right.clone(), name_line));
+    // the second code has no amount
+    assert_eq!(dark_in(right, amount_line), 0);
+}
+
+#[test]
+fn debug_grid() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let plain = code.to_rgba_image(&RenderOptions::default()).unwrap();
+    let grid = code
+        .to_rgba_image(&RenderOptions::default().with_debug_grid(true))
+        .unwrap();
+
+    assert_eq!(grid.dimensions(), plain.dimensions());
+    // the lines run along the top and left edge of every 8 pixel module, through the quiet zone as well
+    for (x, y) in [(0, 0), (8, 3), (3, 8), (32, 33), (33, 32)] {
+        assert_eq!(grid.get_pixel(x, y).0, [200, 200, 200, 255], "{x}, {y}");
+    }
+    // the finder pattern starts after the 4 module quiet zone
+    assert_eq!(grid.get_pixel(1, 1), plain.get_pixel(1, 1));
+    assert_eq!(grid.get_pixel(33, 33).0, [0, 0, 0, 255]);
+    assert!(grid
+        .enumerate_pixels()
+        .filter(|(x, y, _)| x % 8 != 0 && y % 8 != 0)
+        .all(|(x, y, px)| px == plain.get_pixel(x, y)));
+
+    // lines are left out of 1 pixel modules, they would cover the whole code
+    let options = RenderOptions::default().with_module_size(1);
+    assert_eq!(
+        code.to_rgba_image(&options.clone().with_debug_grid(true))
+            .unwrap(),
+        code.to_rgba_image(&options).unwrap()
+    );
+}
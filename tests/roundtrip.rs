@@ -0,0 +1,82 @@
+use epc_qr_code_generator::{Amount, CharacterSet, EpcQr, EpcVersion, LineEnding, Remittance};
+use proptest::prelude::*;
+use proptest::sample::select;
+
+const IBANS: [&str; 4] = [
+    "DE89370400440532013000",
+    "AT611904300234573201",
+    "FR1420041010050500013M02606",
+    "NL91ABNA0417164300",
+];
+
+fn amount() -> impl Strategy<Value = Amount> {
+    (0u32..=999_999_999, 0u8..100)
+        .prop_filter("the amount must not be zero", |(euro, cent)| {
+            (*euro, *cent) != (0, 0)
+        })
+        .prop_map(|(euro, cent)| format!("{euro}.{cent:02}").parse().unwrap())
+}
+
+fn remittance() -> impl Strategy<Value = Remittance> {
+    prop_oneof![
+        "[A-Z0-9]{1,35}".prop_map(Remittance::Reference),
+        "\\PC{1,140}".prop_map(Remittance::Text),
+    ]
+}
+
+fn character_set() -> impl Strategy<Value = CharacterSet> {
+    select(vec![
+        CharacterSet::Utf8,
+        CharacterSet::ISO8859_01,
+        CharacterSet::ISO8859_02,
+        CharacterSet::ISO8859_04,
+        CharacterSet::ISO8859_05,
+        CharacterSet::ISO8859_07,
+        CharacterSet::ISO8859_10,
+        CharacterSet::ISO8859_15,
+    ])
+}
+
+prop_compose! {
+    fn epc_qr()(
+        name in "\\PC{1,70}",
+        iban in select(IBANS.to_vec()),
+        bic in proptest::option::of("[A-Z]{6}[A-Z0-9]{2}([A-Z0-9]{3})?"),
+        v1 in any::<bool>(),
+        amount in proptest::option::of(amount()),
+        purpose in proptest::option::of("[A-Z]{4}"),
+        remittance in proptest::option::of(remittance()),
+        info in proptest::option::of("\\PC{1,70}"),
+        character_set in character_set(),
+        crlf in any::<bool>(),
+    ) -> EpcQr {
+        let version = if v1 && bic.is_some() { EpcVersion::V1 } else { EpcVersion::V2 };
+        let line_ending = if crlf { LineEnding::CrLf } else { LineEnding::Lf };
+        EpcQr::new(name, iban.to_owned())
+            .with_epc_version(Some(version))
+            .with_character_set(character_set)
+            .with_line_ending(line_ending)
+            .with_bic(bic)
+            .with_amount(amount)
+            .with_purpose(purpose)
+            .with_remittance(remittance)
+            .with_info(info)
+    }
+}
+
+proptest! {
+    #[test]
+    fn payload_round_trips(code in epc_qr()) {
+        let payload = code.to_string();
+        let parsed = payload.parse::<EpcQr>().unwrap();
+
+        prop_assert_eq!(parsed.as_fields(), code.as_fields());
+        prop_assert_eq!(parsed.epc_version(), code.epc_version());
+        prop_assert_eq!(parsed.to_string(), payload);
+    }
+
+    #[test]
+    fn generated_codes_are_valid(code in epc_qr()) {
+        prop_assert_eq!(code.validate_verbose(), Vec::new());
+    }
+}
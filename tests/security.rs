@@ -0,0 +1,13 @@
+#![cfg(feature = "security")]
+
+use epc_qr_code_generator::{EpcQr, Warning};
+
+#[test]
+fn confusable_name() {
+    let latin = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    assert!(latin.warnings().is_empty());
+
+    // the 'а' and 'е' are Cyrillic
+    let mixed = EpcQr::new("M\u{430}x Must\u{435}rmann", "DE89370400440532013000");
+    assert_eq!(mixed.warnings(), vec![Warning::ConfusableName]);
+}
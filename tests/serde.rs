@@ -0,0 +1,17 @@
+#![cfg(feature = "serde")]
+
+use epc_qr_code_generator::EpcQr;
+
+#[test]
+fn validation_report_as_json() {
+    let code =
+        EpcQr::new("Max Mustermann", "DE89370400440532013001").with_purpose(Some("GROCERIES"));
+
+    let json = serde_json::to_value(code.validation_report()).unwrap();
+    assert_eq!(json["valid"], false);
+    assert_eq!(
+        json["field_errors"],
+        serde_json::json!(["InvalidIban", { "InvalidPurposeLength": 9 }])
+    );
+    assert_eq!(json["payload_error"], serde_json::Value::Null);
+}
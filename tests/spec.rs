@@ -0,0 +1,32 @@
+use epc_qr_code_generator::{spec, EpcQr, FieldError};
+
+#[test]
+fn payload_uses_spec_constants() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let fields = code.as_fields();
+
+    assert_eq!(fields.len(), spec::MAX_LINES);
+    assert_eq!(fields[0].1.as_deref(), Some(spec::SERVICE_TAG));
+    assert_eq!(fields[1].1.as_deref(), Some(spec::VERSION_2));
+    assert_eq!(fields[3].1.as_deref(), Some(spec::SCT_IDENTIFICATION));
+}
+
+#[test]
+fn field_limits() {
+    let name = "M".repeat(spec::MAX_NAME_LENGTH);
+    assert!(EpcQr::new(name.as_str(), "DE89370400440532013000").is_valid());
+
+    let name = "M".repeat(spec::MAX_NAME_LENGTH + 1);
+    assert_eq!(
+        EpcQr::new(name.as_str(), "DE89370400440532013000").validate_verbose(),
+        vec![FieldError::InvalidNameLength(spec::MAX_NAME_LENGTH + 1)]
+    );
+
+    let info = "i".repeat(spec::MAX_INFO_LENGTH + 1);
+    let code =
+        EpcQr::new("Max Mustermann", "DE89370400440532013000").with_info(Some(info.as_str()));
+    assert_eq!(
+        code.validate_verbose(),
+        vec![FieldError::InvalidInfoLength(spec::MAX_INFO_LENGTH + 1)]
+    );
+}
@@ -0,0 +1,75 @@
+use epc_qr_code_generator::render::RenderOptions;
+use epc_qr_code_generator::{Amount, EpcQr, FieldError, GenerationError, ImageFormat};
+
+fn code() -> EpcQr<&'static str> {
+    EpcQr::new("Max Mustermann", "DE89370400440532013000").with_bic(Some("COBADEFFXXX"))
+}
+
+#[test]
+fn missing_bic() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    assert!(code.is_valid());
+    assert_eq!(
+        code.with_strict_compatibility(true).validate_verbose(),
+        vec![FieldError::MissingBic]
+    );
+}
+
+#[test]
+fn non_sepa_iban() {
+    // a valid IBAN from Brazil
+    let code =
+        EpcQr::new("Max Mustermann", "BR1800360305000010009795493C1").with_bic(Some("COBADEFFXXX"));
+    assert!(code.is_valid());
+    assert_eq!(
+        code.with_strict_compatibility(true).validate_verbose(),
+        vec![FieldError::NonSepaIban {
+            country: "BR".to_owned()
+        }]
+    );
+}
+
+#[test]
+fn lossy_format() {
+    assert!(code().generate_image_bytes(ImageFormat::jpeg()).is_ok());
+    assert!(matches!(
+        code()
+            .with_strict_compatibility(true)
+            .generate_image_bytes(ImageFormat::jpeg()),
+        Err(GenerationError::StrictCompatibility(_))
+    ));
+    assert!(code()
+        .with_strict_compatibility(true)
+        .generate_image_bytes(ImageFormat::png())
+        .is_ok());
+}
+
+#[test]
+fn narrow_quiet_zone() {
+    let options = RenderOptions::default().with_quiet_zone_sides(4, 4, 4, 0);
+    assert!(code()
+        .generate_image_bytes_with_options(ImageFormat::png(), &options)
+        .is_ok());
+    assert!(matches!(
+        code()
+            .with_strict_compatibility(true)
+            .generate_image_bytes_with_options(ImageFormat::png(), &options),
+        Err(GenerationError::StrictCompatibility(_))
+    ));
+}
+
+#[test]
+fn single_decimal_amount() {
+    let single = code().with_amount(Some("12.50".parse::<Amount>().unwrap()));
+    assert!(single.to_string().ends_with("\nEUR12.5"));
+    assert!(single.is_valid());
+    assert_eq!(
+        single.with_strict_compatibility(true).validate_verbose(),
+        vec![FieldError::SingleDecimalAmount { euro: 12, cent: 50 }]
+    );
+
+    for amount in ["12.55", "0.01"] {
+        let code = code().with_amount(Some(amount.parse::<Amount>().unwrap()));
+        assert!(code.with_strict_compatibility(true).is_valid(), "{amount}");
+    }
+}
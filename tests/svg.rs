@@ -0,0 +1,111 @@
+use epc_qr_code_generator::render::RenderOptions;
+use epc_qr_code_generator::{Amount, EpcQr, Remittance};
+use qrcode::Version;
+
+/// The pixels covered by the runs `M{x} {y}h{length}v1h-{length}z` of the first path
+fn covered_pixels(svg: &str) -> Vec<(u32, u32)> {
+    let start = svg.find(r#"<path d=""#).unwrap() + r#"<path d=""#.len();
+    let path = &svg[start..start + svg[start..].find('"').unwrap()];
+    path.split('M')
+        .skip(1)
+        .flat_map(|run| {
+            let (x, rest) = run.split_once(' ').unwrap();
+            let (y, rest) = rest.split_once('h').unwrap();
+            let (length, _) = rest.split_once('v').unwrap();
+            let (x, y, length) = (
+                x.parse::<u32>().unwrap(),
+                y.parse::<u32>().unwrap(),
+                length.parse::<u32>().unwrap(),
+            );
+            (x..x + length).map(move |x| (x, y))
+        })
+        .collect()
+}
+
+#[test]
+fn merged_runs() {
+    let text = "t".repeat(140);
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_remittance(Some(Remittance::Text(text.as_str())));
+    let options = RenderOptions::default()
+        .with_version(Some(Version::Normal(10)))
+        .with_module_size(1);
+
+    let svg = code.to_svg(&options).unwrap();
+    let image = code.to_rgba_image(&options).unwrap();
+    assert!(svg.starts_with(&format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}""#,
+        image.width()
+    )));
+
+    let mut dark = image
+        .enumerate_pixels()
+        .filter(|(_, _, px)| px.0 == [0, 0, 0, 255])
+        .map(|(x, y, _)| (x, y))
+        .collect::<Vec<_>>();
+    let mut covered = covered_pixels(&svg);
+    dark.sort_unstable();
+    covered.sort_unstable();
+    assert_eq!(covered, dark);
+
+    // a naive writer emits one element per dark module, here there are two rects for the background and a single path
+    let elements = svg.matches("<rect").count() + svg.matches("<path").count();
+    assert_eq!(elements, 3);
+    assert!(svg.matches('M').count() < dark.len());
+    let naive = dark
+        .iter()
+        .map(|(x, y)| format!(r#"<rect x="{x}" y="{y}" width="1" height="1"/>"#))
+        .collect::<String>();
+    assert!(
+        2 * svg.len() < naive.len(),
+        "{} vs {} bytes",
+        svg.len(),
+        naive.len()
+    );
+}
+
+#[test]
+fn finder_color() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    let options = RenderOptions::default().with_finder_color(Some([0, 0, 128]));
+
+    let svg = code.to_svg(&options).unwrap();
+    assert_eq!(svg.matches("<path").count(), 2);
+    assert!(svg.contains(r##"fill="#000080""##));
+}
+
+#[test]
+fn caption() {
+    let code = EpcQr::new("Max & Erika Mustermann", "DE89370400440532013000")
+        .with_amount(Some("12.50".parse::<Amount>().unwrap()))
+        .with_remittance(Some(Remittance::Reference("RF18539007547034")));
+    let options = RenderOptions::default().with_svg_caption(true);
+    let info = code.qr_code_info(&options).unwrap();
+
+    let svg = code.to_svg(&options).unwrap();
+    let texts = svg
+        .split("<text")
+        .skip(1)
+        .map(|text| {
+            let start = text.find('>').unwrap() + 1;
+            &text[start..text.find("</text>").unwrap()]
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        texts,
+        [
+            "Beneficiary: Max &amp; Erika Mustermann",
+            "IBAN: DE89370400440532013000",
+            "Amount: EUR 12.50",
+            "Reference: RF18539007547034",
+        ]
+    );
+
+    // 4 lines of 20 pixels for the 16 pixel font, plus half the font size as padding at the bottom
+    let height = info.height + 4 * 20 + 8;
+    assert!(svg.contains(&format!(r#"viewBox="0 0 {} {height}""#, info.width)));
+    assert!(!code
+        .to_svg(&RenderOptions::default())
+        .unwrap()
+        .contains("<text"));
+}
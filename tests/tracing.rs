@@ -0,0 +1,18 @@
+#![cfg(feature = "tracing")]
+
+use epc_qr_code_generator::render::RenderOptions;
+use epc_qr_code_generator::{CharacterSet, EpcQr};
+use tracing_test::traced_test;
+
+#[test]
+#[traced_test]
+fn generation_spans() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_character_set(CharacterSet::ISO8859_01);
+    code.to_rgba_image(&RenderOptions::default()).unwrap();
+
+    assert!(logs_contain("validate_verbose"));
+    assert!(logs_contain("data{character_set=iso-8859-1}"));
+    assert!(logs_contain("encoded payload bytes=52 max_bytes=331"));
+    assert!(logs_contain("built QR-Code"));
+}
@@ -0,0 +1,8 @@
+#![deny(unused_must_use)]
+
+use epc_qr_code_generator::EpcQr;
+
+fn main() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    code.with_info(Some("Thanks"));
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "unicode-normalization")]
+
+use epc_qr_code_generator::{EpcQr, FieldError, Remittance};
+
+#[test]
+fn decomposed_name_fits_after_normalization() {
+    // 70 times 'e' followed by a combining acute accent, 140 characters that compose to 70 times 'é'
+    let decomposed = "e\u{301}".repeat(70);
+    let composed = "\u{e9}".repeat(70);
+
+    let code = EpcQr::new(decomposed.as_str(), "DE89370400440532013000");
+    assert_eq!(
+        code.validate_verbose(),
+        vec![FieldError::InvalidNameLength(140)]
+    );
+
+    let code = code.with_normalize_unicode(true);
+    assert!(code.validate_verbose().is_empty());
+    assert_eq!(
+        code.payload_bytes().unwrap(),
+        EpcQr::new(composed.as_str(), "DE89370400440532013000")
+            .payload_bytes()
+            .unwrap()
+    );
+}
+
+#[test]
+fn remittance_and_info() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_remittance(Some(Remittance::Text("Caf\u{65}\u{301}")))
+        .with_info(Some("Cre\u{300}me"))
+        .with_normalize_unicode(true);
+
+    assert!(code.to_string().ends_with("\nCaf\u{e9}\nCr\u{e8}me"));
+}
@@ -0,0 +1,358 @@
+use epc_qr_code_generator::validator::{EpcStandardValidator, Validator};
+use epc_qr_code_generator::{
+    CharacterSet, EpcQr, EpcQrRef, EpcVersion, FieldError, InvalidEpcCode, Remittance,
+};
+
+#[test]
+fn is_valid() {
+    assert!(EpcQr::new("Max Mustermann", "DE89370400440532013000").is_valid());
+    // wrong check digits
+    assert!(!EpcQr::new("Max Mustermann", "DE00370400440532013000").is_valid());
+}
+
+#[test]
+fn combined_too_large() {
+    let name = "ä".repeat(70);
+    let text = "ä".repeat(140);
+    let code = EpcQr::new(name.as_str(), "DE89370400440532013000")
+        .with_remittance(Some(Remittance::Text(text.as_str())));
+
+    assert!(code.validate_verbose().is_empty());
+    assert!(matches!(
+        code.validate(),
+        Err(InvalidEpcCode::CombinedTooLarge { max: 331, .. })
+    ));
+}
+
+#[test]
+fn custom_validator() {
+    #[derive(Debug)]
+    struct ShortNames;
+
+    impl Validator for ShortNames {
+        fn validate(&self, code: &EpcQrRef<'_>) -> Vec<FieldError> {
+            let mut errors = EpcStandardValidator.validate(code);
+            let name = code
+                .as_fields()
+                .into_iter()
+                .find_map(|(label, value)| (label == "name").then_some(value))
+                .flatten()
+                .unwrap_or_default();
+            let length = name.chars().count();
+            if 35 < length {
+                errors.push(FieldError::InvalidNameLength(length));
+            }
+            errors
+        }
+    }
+
+    let name = "M".repeat(40);
+    let code = EpcQr::new(name.as_str(), "DE89370400440532013000");
+    assert!(code.is_valid());
+
+    let code = code.with_validator(Box::new(ShortNames));
+    assert_eq!(
+        code.validate_verbose(),
+        vec![FieldError::InvalidNameLength(40)]
+    );
+    assert!(!code.is_valid());
+}
+
+#[test]
+fn fields_and_size_separately() {
+    let name = "ä".repeat(70);
+    let text = "ä".repeat(140);
+    let oversized = EpcQr::new(name.as_str(), "DE89370400440532013000")
+        .with_remittance(Some(Remittance::Text(text.as_str())));
+    assert!(oversized.validate_fields().is_ok());
+    assert!(matches!(
+        oversized.validate_size(),
+        Err(InvalidEpcCode::CombinedTooLarge { max: 331, .. })
+    ));
+
+    let invalid_iban = EpcQr::new("Max Mustermann", "DE00370400440532013000");
+    assert!(invalid_iban.validate_size().is_ok());
+    assert!(matches!(
+        invalid_iban.validate_fields(),
+        Err(InvalidEpcCode::InvalidFieldLength { .. })
+    ));
+}
+
+#[test]
+fn example_is_valid() {
+    let example = EpcQr::example();
+    assert!(example.validate_verbose().is_empty());
+    assert!(example.validate().is_ok());
+    assert!(epc_qr_code_generator::iban_is_valid(
+        example.beneficiary_account()
+    ));
+    // every field is set, except the text which can't be combined with the reference
+    assert!(example
+        .as_fields()
+        .iter()
+        .take(12)
+        .all(|(label, value)| value.is_some() || *label == "text"));
+}
+
+#[test]
+fn try_new() {
+    let code = EpcQr::try_new("Max Mustermann", "DE89370400440532013000").unwrap();
+    assert_eq!(code, EpcQr::new("Max Mustermann", "DE89370400440532013000"));
+
+    // the last digit of the account number is changed, so the check digits don't match
+    let err = EpcQr::try_new("Max Mustermann", "DE89370400440532013001").unwrap_err();
+    assert!(matches!(err, InvalidEpcCode::InvalidFieldLength { .. }));
+    assert_eq!(err.field_names(), ["beneficiary_account"]);
+
+    let err = EpcQr::try_new("", "DE89370400440532013000").unwrap_err();
+    assert_eq!(err.field_names(), ["beneficiary_name"]);
+}
+
+#[test]
+fn remittance_boundaries() {
+    assert_eq!(Remittance::MAX_REFERENCE_LEN, 35);
+    assert_eq!(Remittance::MAX_TEXT_LEN, 140);
+
+    for (length, valid) in [(0, false), (1, true), (35, true), (36, false)] {
+        let reference = "r".repeat(length);
+        let remittance = Remittance::Reference(reference.as_str());
+        assert_eq!(
+            remittance.validate().is_ok(),
+            valid,
+            "reference of {length}"
+        );
+        let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+            .with_remittance(Some(remittance));
+        assert_eq!(code.is_valid(), valid, "reference of {length}");
+    }
+
+    for (length, valid) in [(0, false), (1, true), (140, true), (141, false)] {
+        let text = "t".repeat(length);
+        let remittance = Remittance::Text(text.as_str());
+        assert_eq!(remittance.validate().is_ok(), valid, "text of {length}");
+        let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+            .with_remittance(Some(remittance));
+        assert_eq!(code.is_valid(), valid, "text of {length}");
+    }
+
+    let err = Remittance::Text("t".repeat(141)).validate().unwrap_err();
+    assert_eq!(err.field_names(), ["remittance"]);
+}
+
+#[test]
+fn validation_report() {
+    let code = EpcQr::new("", "DE89370400440532013001")
+        .with_bic(Some("NOTABIC"))
+        .with_purpose(Some("GROCERIES"));
+
+    let report = code.validation_report();
+    assert!(!report.valid);
+    assert_eq!(
+        report.field_errors,
+        vec![
+            FieldError::InvalidBicLength(7),
+            FieldError::InvalidNameLength(0),
+            FieldError::InvalidIban,
+            FieldError::InvalidPurposeLength(9),
+        ]
+    );
+    assert_eq!(report.payload_error, None);
+
+    // the fields are valid on their own, but the euro sign isn't part of ISO 8859-1
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000")
+        .with_info(Some("5 \u{20ac} Rabatt"))
+        .with_character_set(CharacterSet::ISO8859_01);
+    let report = code.validation_report();
+    assert!(!report.valid);
+    assert!(report.field_errors.is_empty());
+    assert!(report.payload_error.unwrap().contains('\u{20ac}'));
+
+    let report = EpcQr::example().validation_report();
+    assert!(report.valid);
+    assert!(report.field_errors.is_empty());
+}
+
+#[test]
+fn try_with_character_set() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000").with_info(Some("Grüße"));
+
+    let latin1 = code
+        .clone()
+        .try_with_character_set(CharacterSet::ISO8859_01)
+        .unwrap();
+    assert_eq!(latin1.character_set(), &CharacterSet::ISO8859_01);
+
+    let code = code.with_info(Some("5 \u{20ac} Rabatt"));
+    assert!(matches!(
+        code.clone()
+            .try_with_character_set(CharacterSet::ISO8859_01),
+        Err(InvalidEpcCode::UnencodableCharacter {
+            character: '\u{20ac}',
+            character_set: CharacterSet::ISO8859_01,
+        })
+    ));
+    // ISO 8859-15 replaced some characters of ISO 8859-1 with the euro sign
+    assert!(code
+        .clone()
+        .try_with_character_set(CharacterSet::ISO8859_15)
+        .is_ok());
+
+    // the non-failing variant defers the check to generating the code
+    let deferred = code.with_character_set(CharacterSet::ISO8859_01);
+    assert!(deferred.payload_bytes().is_err());
+}
+
+#[test]
+fn iban_and_bic_formats() {
+    use epc_qr_code_generator::{bic_is_valid, iban_is_valid};
+
+    for iban in [
+        "DE89370400440532013000",
+        "GB82WEST12345698765432",
+        "NL91ABNA0417164300",
+    ] {
+        assert!(iban_is_valid(iban), "{iban}");
+    }
+    for iban in [
+        // the print format isn't allowed in the payload
+        "DE89 3704 0044 0532 0130 00",
+        "de89370400440532013000",
+        "DE88370400440532013000",
+        "DE8937040044",
+        "DE89-370400440532013000",
+    ] {
+        assert!(!iban_is_valid(iban), "{iban}");
+    }
+    // codes write the IBAN in the electronic format and validate that
+    let spaced = EpcQr::new("Max Mustermann", "de89 3704 0044 0532 0130 00");
+    assert!(spaced.is_valid());
+    assert_eq!(
+        spaced.to_string().lines().nth(6),
+        Some("DE89370400440532013000")
+    );
+    assert_eq!(
+        EpcQr::new("Max Mustermann", "DE88 3704 0044 0532 0130 00").validate_verbose(),
+        vec![FieldError::InvalidIban]
+    );
+
+    for bic in ["COBADEFFXXX", "COBADEFF", "DEUTDE2H", "BNPAFRPP123"] {
+        assert!(bic_is_valid(bic), "{bic}");
+    }
+    for bic in [
+        "COBADEF",
+        "COBADEFFXX",
+        "cobadeff",
+        "CO8ADEFF",
+        "COBAD3FF",
+        "COBADEFF-XX",
+    ] {
+        assert!(!bic_is_valid(bic), "{bic}");
+    }
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    assert_eq!(
+        code.clone().with_bic(Some("COBADEF")).validate_verbose(),
+        vec![FieldError::InvalidBicLength(7)]
+    );
+    assert_eq!(
+        code.with_bic(Some("CO8ADEFF")).validate_verbose(),
+        vec![FieldError::InvalidBic]
+    );
+}
+
+#[test]
+fn all_violations_listed() {
+    let info = "i".repeat(71);
+    let code = EpcQr::new("", "DE00370400440532013000")
+        .with_bic(Some("COBADEF"))
+        .with_purpose(Some("GROCERIES"))
+        .with_info(Some(info.as_str()));
+
+    assert_eq!(
+        code.validate_verbose(),
+        vec![
+            FieldError::InvalidBicLength(7),
+            FieldError::InvalidNameLength(0),
+            FieldError::InvalidIban,
+            FieldError::InvalidPurposeLength(9),
+            FieldError::InvalidInfoLength(71),
+        ]
+    );
+    assert_eq!(
+        code.validate().unwrap_err().field_names(),
+        [
+            "bic",
+            "beneficiary_name",
+            "beneficiary_account",
+            "purpose",
+            "info"
+        ]
+    );
+}
+
+#[test]
+fn bic_required_by_version() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+
+    let v1 = code.clone().with_epc_version(Some(EpcVersion::V1));
+    assert!(v1.bic_required());
+    assert_eq!(v1.validate_verbose(), vec![FieldError::MissingBic]);
+    assert!(v1.with_bic(Some("COBADEFFXXX")).is_valid());
+
+    let v2 = code.with_epc_version(Some(EpcVersion::V2));
+    assert!(!v2.bic_required());
+    assert!(v2.validate_verbose().is_empty());
+}
+
+#[test]
+fn to_version() {
+    let code = EpcQr::new("Max Mustermann", "DE89370400440532013000");
+    assert!(matches!(
+        code.clone().to_version(EpcVersion::V1),
+        Err(InvalidEpcCode::InvalidFieldLength {
+            invalid_bic: true,
+            ..
+        })
+    ));
+
+    let v2 = code.clone().to_version(EpcVersion::V2).unwrap();
+    assert_eq!(v2.epc_version(), EpcVersion::V2);
+    assert!(v2.to_string().starts_with("BCD\n002\n"));
+
+    let with_bic = code.with_bic(Some("COBADEFFXXX"));
+    let v1 = with_bic.to_version(EpcVersion::V1).unwrap();
+    assert_eq!(v1.epc_version(), EpcVersion::V1);
+    assert!(v1
+        .to_string()
+        .starts_with("BCD\n001\n1\nSCT\nCOBADEFFXXX\n"));
+    assert!(v1.is_valid());
+
+    // the BIC is kept when converting back
+    let v2 = v1.to_version(EpcVersion::V2).unwrap();
+    assert_eq!(v2.bic(), Some("COBADEFFXXX"));
+    assert!(v2
+        .to_string()
+        .starts_with("BCD\n002\n1\nSCT\nCOBADEFFXXX\n"));
+}
+
+#[test]
+fn find_iban() {
+    use epc_qr_code_generator::find_iban;
+
+    assert_eq!(
+        find_iban("Please transfer to IBAN: DE89 3704 0044 0532 0130 00, thank you!").as_deref(),
+        Some("DE89370400440532013000")
+    );
+    assert_eq!(
+        find_iban("(GB82WEST12345698765432)").as_deref(),
+        Some("GB82WEST12345698765432")
+    );
+    assert_eq!(find_iban("Reference 12345, due 2024-01-31"), None);
+    assert_eq!(find_iban(""), None);
+    // wrong check digits
+    assert_eq!(find_iban("IBAN: DE88 3704 0044 0532 0130 00"), None);
+    // DE89370400440532013000 followed by 0163 passes the check digits as well, but is too long for Germany
+    assert_eq!(
+        find_iban("DE89 3704 0044 0532 0130 00 0163 Invoice").as_deref(),
+        Some("DE89370400440532013000")
+    );
+}